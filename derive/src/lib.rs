@@ -0,0 +1,170 @@
+// Derive macros for librstb. They only need to understand simple item shapes, so the input is
+// parsed directly from the token stream.
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Derives `librstb::hdl_enum::HdlEnum` for an enum of unit variants. Each variant maps to the
+/// HDL literal of the same name, compared ignoring case and underscores, unless it is renamed
+/// with `#[hdl(name = "LITERAL")]`. If librstb is renamed in Cargo.toml, its new name is given
+/// with `#[hdl(crate = "path")]` on the enum.
+#[proc_macro_derive(HdlEnum, attributes(hdl))]
+pub fn derive_hdl_enum(input: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let pos = tokens
+        .iter()
+        .position(|t| matches!(t, TokenTree::Ident(i) if i.to_string() == "enum"))
+        .expect("HdlEnum can only be derived for enums.");
+    let name = tokens[pos + 1].to_string();
+    let mut krate = "::librstb".to_string();
+    for (i, token) in tokens[..pos].iter().enumerate() {
+        if let (TokenTree::Punct(p), Some(TokenTree::Group(attr))) = (token, tokens.get(i + 1)) {
+            if p.as_char() == '#' {
+                if let Some(path) = hdl_attr(attr.stream(), "crate") {
+                    krate = path;
+                }
+            }
+        }
+    }
+    let body = match tokens.get(pos + 2) {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => g.stream(),
+        _ => panic!("HdlEnum can't be derived for generic enum {}.", name),
+    };
+    let variants = parse_variants(&name, body);
+    if variants.is_empty() {
+        panic!(
+            "HdlEnum can't be derived for enum {} without variants.",
+            name
+        );
+    }
+    if let Some(msg) = duplicate_literal(&name, &variants) {
+        return format!("compile_error!({:?});", msg).parse().unwrap();
+    }
+
+    let literal_arms: String = variants
+        .iter()
+        .map(|(v, lit)| format!("{}::{} => {:?},", name, v, lit))
+        .collect();
+    let from_arms: String = variants
+        .iter()
+        .map(|(v, lit)| format!("{:?} => Some({}::{}),", normalize(lit), name, v))
+        .collect();
+    format!(
+        "impl {krate}::hdl_enum::HdlEnum for {name} {{
+            fn literal(&self) -> &'static str {{
+                match self {{ {literal_arms} }}
+            }}
+            fn from_literal(literal: &str) -> ::std::option::Option<Self> {{
+                match literal.replace('_', \"\").to_ascii_lowercase().as_str() {{
+                    {from_arms}
+                    _ => None,
+                }}
+            }}
+        }}"
+    )
+    .parse()
+    .unwrap()
+}
+
+// Variant names with their HDL literal
+fn parse_variants(enum_name: &str, body: TokenStream) -> Vec<(String, String)> {
+    let mut variants = Vec::new();
+    let mut rename = None;
+    let mut variant: Option<String> = None;
+    let mut tokens = body.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            // attribute
+            TokenTree::Punct(p) if p.as_char() == '#' && variant.is_none() => {
+                if let Some(TokenTree::Group(attr)) = tokens.next() {
+                    if let Some(name) = hdl_attr(attr.stream(), "name") {
+                        rename = Some(name);
+                    }
+                }
+            }
+            TokenTree::Punct(p) if p.as_char() == ',' => {
+                if let Some(v) = variant.take() {
+                    let literal = rename.take().unwrap_or_else(|| v.clone());
+                    variants.push((v, literal));
+                }
+            }
+            TokenTree::Ident(i) if variant.is_none() => {
+                variant = Some(i.to_string());
+                if let Some(TokenTree::Group(_)) = tokens.peek() {
+                    panic!(
+                        "HdlEnum can only be derived for enums with unit variants, {}::{} has fields.",
+                        enum_name, i
+                    );
+                }
+            }
+            // explicit discriminant
+            _ => (),
+        }
+    }
+    if let Some(v) = variant {
+        let literal = rename.unwrap_or_else(|| v.clone());
+        variants.push((v, literal));
+    }
+    variants
+}
+
+// Value of a `hdl(key = "...")` attribute
+fn hdl_attr(attr: TokenStream, key: &str) -> Option<String> {
+    let mut tokens = attr.into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "hdl" => (),
+        _ => return None,
+    }
+    let args = match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g.stream(),
+        _ => panic!("Expected #[hdl({} = \"...\")].", key),
+    };
+    let args: Vec<TokenTree> = args.into_iter().collect();
+    match args.as_slice() {
+        [TokenTree::Ident(k), TokenTree::Punct(eq), TokenTree::Literal(lit)]
+            if k.to_string() == key && eq.as_char() == '=' =>
+        {
+            let lit = lit.to_string();
+            Some(lit.trim_matches('"').to_string())
+        }
+        _ => panic!("Expected #[hdl({} = \"...\")].", key),
+    }
+}
+
+fn normalize(literal: &str) -> String {
+    literal.replace('_', "").to_ascii_lowercase()
+}
+
+// Variants whose literals only differ in case and underscores can't be told apart
+fn duplicate_literal(enum_name: &str, variants: &[(String, String)]) -> Option<String> {
+    variants.iter().enumerate().find_map(|(i, (a, lit_a))| {
+        variants[..i]
+            .iter()
+            .find(|(_, lit_b)| normalize(lit_a) == normalize(lit_b))
+            .map(|(b, _)| {
+                format!(
+                    "HdlEnum variants {}::{} and {}::{} map to the same HDL literal {}.",
+                    enum_name, b, enum_name, a, lit_a
+                )
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variants(v: &[(&str, &str)]) -> Vec<(String, String)> {
+        v.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+    }
+
+    #[test]
+    fn duplicate_literals() {
+        let ok = variants(&[("Idle", "Idle"), ("ReadData", "READ_DATA"), ("Rd", "READ")]);
+        assert_eq!(duplicate_literal("State", &ok), None);
+        let dup = variants(&[("Idle", "Idle"), ("ReadData", "ReadData"), ("Read", "READ_DATA")]);
+        assert_eq!(
+            duplicate_literal("State", &dup).unwrap(),
+            "HdlEnum variants State::ReadData and State::Read map to the same HDL literal READ_DATA."
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use librstb::prelude::*;
+
+#[derive(Debug, PartialEq, HdlEnum)]
+enum State {
+    Idle,
+    ReadData,
+    #[hdl(name = "WR")]
+    Write,
+    Done = 7,
+}
+
+mod renamed {
+    // librstb under another name, like a dependency renamed in Cargo.toml
+    use librstb as rstb;
+    use rstb::prelude::*;
+
+    #[derive(Debug, PartialEq, HdlEnum)]
+    #[hdl(crate = "rstb")]
+    pub enum Mode {
+        Fast,
+        Slow,
+    }
+}
+
+#[test]
+fn literals() {
+    assert_eq!(State::Idle.literal(), "Idle");
+    assert_eq!(State::ReadData.literal(), "ReadData");
+    assert_eq!(State::Write.literal(), "WR");
+    assert_eq!(State::Done.literal(), "Done");
+}
+
+#[test]
+fn from_literal_ignores_case_and_underscores() {
+    assert_eq!(State::from_literal("IDLE"), Some(State::Idle));
+    assert_eq!(State::from_literal("READ_DATA"), Some(State::ReadData));
+    assert_eq!(State::from_literal("wr"), Some(State::Write));
+    // renamed variants are only known by their HDL literal
+    assert_eq!(State::from_literal("WRITE"), None);
+    assert_eq!(State::from_literal("BUSY"), None);
+}
+
+#[test]
+fn renamed_crate() {
+    use renamed::Mode;
+    assert_eq!(Mode::Slow.literal(), "Slow");
+    assert_eq!(Mode::from_literal("FAST"), Some(Mode::Fast));
+}
@@ -2,4 +2,9 @@ bindgen ../src/sv_vpi_user.h -o ../src/sv_vpi_user.rs --no-layout-tests --with-d
     --allowlist-var vpiIntVar \
     --allowlist-var vpiLongIntVar \
     --allowlist-var vpiBitVar \
-    --allowlist-var vpiShortRealVar
\ No newline at end of file
+    --allowlist-var vpiShortRealVar \
+    --allowlist-var vpiStringVar \
+    --allowlist-var vpiEnumVar \
+    --allowlist-var vpiEnumNet \
+    --allowlist-var vpiEnumConst \
+    --allowlist-var vpiTypespec
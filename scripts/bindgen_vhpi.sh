@@ -5,11 +5,28 @@ bindgen ../src/vhpi_user.h -o ../src/vhpi_user.rs --no-layout-tests --with-deriv
     --allowlist-var vhpiCbAfterDelay \
     --allowlist-var vhpiCbLastKnownDeltaCycle \
     --allowlist-var vhpiCbEndOfTimeStep \
+    --allowlist-var vhpiCbNextTimeStep \
+    --allowlist-var vhpiCbEndOfProcesses \
+    --allowlist-var vhpiCbTimeOut \
     --allowlist-var vhpiReturnCb \
+    --allowlist-var vhpiU \
+    --allowlist-var vhpiX \
+    --allowlist-var vhpi0 \
+    --allowlist-var vhpi1 \
+    --allowlist-var vhpiZ \
+    --allowlist-var vhpiW \
+    --allowlist-var vhpiL \
+    --allowlist-var vhpiH \
+    --allowlist-var vhpiDontCare \
+    \
+    --allowlist-type vhpiClassKindT \
+    --allowlist-type vhpiModeT \
+    --allowlist-type vhpiSimControlT \
     \
     --allowlist-function vhpi_register_cb \
     --allowlist-function vhpi_remove_cb \
     --allowlist-function vhpi_handle_by_name \
+    --allowlist-function vhpi_handle_by_index \
     --allowlist-function vhpi_handle \
     --allowlist-function vhpi_put_value \
     --allowlist-function vhpi_get_value \
@@ -20,4 +37,5 @@ bindgen ../src/vhpi_user.h -o ../src/vhpi_user.rs --no-layout-tests --with-deriv
     --allowlist-function vhpi_iterator \
     --allowlist-function vhpi_scan \
     --allowlist-function vhpi_check_error \
+    --allowlist-function vhpi_control \
     --allowlist-function vhpi_printf
\ No newline at end of file
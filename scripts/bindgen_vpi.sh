@@ -5,6 +5,9 @@ bindgen ../src/vpi_user.h -o ../src/vpi_user.rs --no-layout-tests --with-derive-
     --allowlist-var cbAfterDelay \
     --allowlist-var cbReadWriteSynch \
     --allowlist-var cbReadOnlySynch \
+    --allowlist-var cbNextSimTime \
+    --allowlist-var cbAtStartOfSimTime \
+    --allowlist-var cbNBASynch \
     --allowlist-var vpiSimTime \
     --allowlist-var vpiTimePrecision \
     --allowlist-var vpiSuppressTime \
@@ -16,9 +19,13 @@ bindgen ../src/vpi_user.h -o ../src/vpi_user.rs --no-layout-tests --with-derive-
     --allowlist-var vpiSigned \
     --allowlist-var vpiBinStrVal \
     --allowlist-var vpiIntVal \
+    --allowlist-var vpiRealVal \
     --allowlist-var vpiStringVal \
+    --allowlist-var vpiVectorVal \
     --allowlist-var vpiSuppressVal \
+    --allowlist-var vpiNoDelay \
     --allowlist-var vpiInertialDelay \
+    --allowlist-var vpiTransportDelay \
     --allowlist-var vpiForceFlag \
     --allowlist-var vpiReleaseFlag \
     --allowlist-var vpiArray \
@@ -28,14 +35,49 @@ bindgen ../src/vpi_user.h -o ../src/vpi_user.rs --no-layout-tests --with-derive-
     --allowlist-var vpiNetBit \
     --allowlist-var vpiReg \
     --allowlist-var vpiRegBit \
+    --allowlist-var vpiParameter \
+    --allowlist-var vpiSpecParam \
     --allowlist-var vpiPort \
     --allowlist-var vpiPortBit \
     --allowlist-var vpiMemoryWord \
+    --allowlist-var vpiMemory \
+    --allowlist-var vpiNetArray \
+    --allowlist-var vpiRegArray \
     --allowlist-var vpiModule \
+    --allowlist-var vpiNamedBegin \
+    --allowlist-var vpiNamedFork \
+    --allowlist-var vpiGenScope \
+    --allowlist-var vpiInternalScope \
+    --allowlist-var vpiLeftRange \
+    --allowlist-var vpiRightRange \
+    --allowlist-var vpiVariables \
+    --allowlist-var vpiDirection \
+    --allowlist-var vpiInput \
+    --allowlist-var vpiOutput \
+    --allowlist-var vpiInout \
+    --allowlist-var vpiStop \
+    --allowlist-var vpiError \
+    --allowlist-var vpiFinish \
+    --allowlist-var vpiReset \
+    --allowlist-var vpiConstant \
+    --allowlist-var vpiConstType \
+    --allowlist-var vpiRealConst \
+    --allowlist-var vpiStringConst \
+    --allowlist-var vpiSysTfCall \
+    --allowlist-var vpiArgument \
+    --allowlist-var vpiSysTask \
+    --allowlist-var vpiSysFunc \
+    --allowlist-var vpiIntFunc \
+    --allowlist-var vpiRealFunc \
+    --allowlist-var vpiSizedFunc \
+    --allowlist-var vpiSizedSignedFunc \
     \
     --allowlist-function vpi_register_cb \
     --allowlist-function vpi_remove_cb \
+    --allowlist-function vpi_register_systf \
     --allowlist-function vpi_handle_by_name \
+    --allowlist-function vpi_handle_by_index \
+    --allowlist-function vpi_handle \
     --allowlist-function vpi_put_value \
     --allowlist-function vpi_get_value \
     --allowlist-function vpi_get_time \
@@ -43,5 +85,8 @@ bindgen ../src/vpi_user.h -o ../src/vpi_user.rs --no-layout-tests --with-derive-
     --allowlist-function vpi_get \
     --allowlist-function vpi_iterate \
     --allowlist-function vpi_scan \
+    --allowlist-function vpi_chk_error \
     --allowlist-function vpi_free_object \
+    --allowlist-function vpi_get_vlog_info \
+    --allowlist-function vpi_control \
     --allowlist-function vpi_printf
\ No newline at end of file
@@ -1,3 +1,5 @@
+#![allow(static_mut_refs)]
+
 use crate::prelude::*;
 use crate::seamap::SeaMap;
 use futures::future::BoxFuture;
@@ -96,26 +98,41 @@ impl AssertionContext {
             .expect("Could not find history in context.");
         hist[clocks].clone()
     }
-    fn hist(&self) -> Ref<SigHist> {
+    fn hist(&self) -> Ref<'_, SigHist> {
         self.0.history.get()
     }
     pub fn rose(&self, sig: SimObject) -> bool {
-        sig.u32() == 1 && self.sig_hist(sig, 1) != Val::Int(1)
+        lsb_x01(&sig.value()) == Logic::One && lsb_x01(&self.sig_hist(sig, 1)) != Logic::One
     }
     pub fn fell(&self, sig: SimObject) -> bool {
-        sig.u32() == 0 && self.sig_hist(sig, 1) != Val::Int(0)
+        lsb_x01(&sig.value()) == Logic::Zero && lsb_x01(&self.sig_hist(sig, 1)) != Logic::Zero
     }
     pub fn stable(&self, sig: SimObject) -> bool {
-        self.sig_hist(sig, 1) == Val::Int(sig.u32())
+        self.sig_hist(sig, 1) == sig.value()
     }
     pub fn changed(&self, sig: SimObject) -> bool {
-        self.sig_hist(sig, 1) != Val::Int(sig.u32())
+        self.sig_hist(sig, 1) != sig.value()
     }
-    fn hist_mut(&self) -> RefMut<SigHist> {
+    fn hist_mut(&self) -> RefMut<'_, SigHist> {
         self.0.history.get_mut()
     }
 }
 
+// Least significant bit of a signal value as 0, 1 or X, which `rose()` and `fell()` compare like
+// SystemVerilog's $rose and $fell
+fn lsb_x01(val: &Val) -> Logic {
+    match val {
+        Val::Int(v) => Logic::from(v & 1 == 1),
+        Val::Signed(v) => Logic::from(v & 1 == 1),
+        Val::Logic(l) => match l.get(0).map(Logic::to_x01z) {
+            Some(Logic::Zero) => Logic::Zero,
+            Some(Logic::One) => Logic::One,
+            _ => Logic::X,
+        },
+        _ => Logic::X,
+    }
+}
+
 pub struct Assertion {
     name: String,
     enabled: RstbObj<bool>,
@@ -140,7 +157,7 @@ impl Assertion {
                             val_vec.pop_back();
                             let obj = SimObject::from_handle(*hdl as usize)
                                 .expect("Could not resolve SimObject from handle.");
-                            val_vec.push_front(Val::Int(obj.u32()));
+                            val_vec.push_front(obj.value());
                         }
                     }
                     Ok(Val::None)
@@ -303,3 +320,53 @@ pub fn print_assertion_stats() {
         }
     }
 }
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+
+    // context whose history holds `past` as value of `sig` one trigger ago
+    fn ctx(sig: SimObject, past: Val) -> AssertionContext {
+        let mut history = IntMap::new();
+        history.insert(sig.handle() as u64, VecDeque::from(vec![Val::None, past]));
+        AssertionContext(AssertionContextInner {
+            trigger: Trigger::read_only(),
+            history: RstbObj::new(SigHist {
+                enabled: true,
+                history,
+                task_hdl: None,
+            }),
+            dut: SimObject::get_root().unwrap(),
+        })
+    }
+
+    #[test]
+    fn rose_and_fell_with_x() {
+        let r = MockSim::new("top").signal("a", 1).run(|dut| {
+            async move {
+                let a = dut.c("a");
+                let x = || Val::Logic("x".parse().unwrap());
+                a.set(1);
+                Trigger::read_only().await;
+                // X -> 1 rises, like 0 -> 1
+                assert!(ctx(a, x()).rose(a) && ctx(a, Val::Int(0)).rose(a));
+                assert!(!ctx(a, Val::Int(1)).rose(a) && !ctx(a, x()).fell(a));
+                assert!(ctx(a, x()).changed(a) && ctx(a, Val::Int(1)).stable(a));
+                Trigger::timer(1, "ns").await;
+                a.set_bin("x");
+                Trigger::read_only().await;
+                // X is neither 1 nor 0, although it is read as 0
+                assert!(!ctx(a, Val::Int(1)).fell(a) && !ctx(a, Val::Int(0)).rose(a));
+                assert!(ctx(a, x()).stable(a) && ctx(a, Val::Int(1)).changed(a));
+                Trigger::timer(1, "ns").await;
+                a.set(0);
+                Trigger::read_only().await;
+                assert!(ctx(a, x()).fell(a) && ctx(a, Val::Int(1)).fell(a));
+                assert!(!ctx(a, Val::Int(0)).fell(a));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
@@ -0,0 +1,286 @@
+// DPI-C bridge. SystemVerilog imports the functions below and calls the Rust handlers
+// registered with `register_dpi()` by name:
+//
+//   import "DPI-C" context function int rstb_dpi_int(input string name, input int arg);
+//   import "DPI-C" context function real rstb_dpi_real(input string name, input real arg);
+//   import "DPI-C" context function void rstb_dpi_bits(input string name, input int width,
+//       input bit [127:0] arg, output bit [127:0] ret);
+//   import "DPI-C" context function void rstb_dpi_logic(input string name, input int width,
+//       input logic [127:0] arg, output logic [127:0] ret);
+//
+// All imports of a C function must have the same signature, so `arg` and `ret` are declared
+// with the widest vector a design needs, 128 bits above. `width` is the number of bits the
+// handler sees, e.g. `rstb_dpi_bits("crc", 16, data, crc)` passes the lower 16 bits of `data`.
+// Bits of `ret` above `width` are left undefined. Handlers are executed like system tasks:
+// tasks awaiting `Trigger::dpi()` continue before the call returns to SV.
+//
+// Panicking would unwind into the simulator: unknown handlers and return values which don't fit
+// the function are logged, and 0 or X is returned instead.
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::Arc;
+
+use crate::rstb_obj::RstbObjSafe;
+use crate::sim_if::{bin_to_vec, vec_to_bin, VecWord, SIM_IF};
+use crate::trigger;
+use crate::value::Val;
+
+// svdpi.h
+pub type SvBitVecVal = u32;
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SvLogicVecVal {
+    pub aval: u32,
+    pub bval: u32,
+}
+
+type DpiFn = Arc<dyn Fn(&[Val]) -> Val + Send + Sync>;
+
+lazy_static! {
+    static ref HANDLERS: RstbObjSafe<HashMap<String, DpiFn>> = RstbObjSafe::new(HashMap::new());
+}
+
+/// Registers the handler `name` for calls from SystemVerilog through the `rstb_dpi_*`
+/// functions. `f` receives the call's argument and returns the value passed back to SV.
+pub fn register_dpi(name: &str, f: impl Fn(&[Val]) -> Val + Send + Sync + 'static) {
+    HANDLERS.with_mut(|mut handlers| handlers.insert(name.to_string(), Arc::new(f)));
+}
+
+pub(crate) fn is_registered(name: &str) -> bool {
+    HANDLERS.get().contains_key(name)
+}
+
+// Executes the handler, then wakes the tasks awaiting the call. Returns `Val::Error` for
+// unknown handlers.
+fn call(name: *const c_char, args: Vec<Val>) -> Val {
+    let name = unsafe { CStr::from_ptr(name) }
+        .to_string_lossy()
+        .into_owned();
+    let f = HANDLERS.get().get(&name).cloned();
+    let f = match f {
+        Some(f) => f,
+        None => {
+            SIM_IF.log(&format!("No DPI handler registered for {}.", name));
+            return Val::Error;
+        }
+    };
+    let ret = f(&args);
+    trigger::react_call(&name, args);
+    ret
+}
+
+fn log_invalid(ret: &Val, function: &str) {
+    if *ret != Val::Error {
+        SIM_IF.log(&format!("Can't return {:?} from {}().", ret, function));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rstb_dpi_int(name: *const c_char, arg: i32) -> i32 {
+    match call(name, vec![Val::Signed(arg)]) {
+        Val::None => 0,
+        ret => match to_words(&ret, 32) {
+            Some(words) => words[0].aval as i32,
+            None => {
+                log_invalid(&ret, "rstb_dpi_int");
+                0
+            }
+        },
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rstb_dpi_real(name: *const c_char, arg: f64) -> f64 {
+    match call(name, vec![Val::Float(arg)]) {
+        Val::Float(v) => v,
+        Val::Int(v) => v as f64,
+        Val::Signed(v) => v as f64,
+        Val::None => 0.0,
+        ret => {
+            log_invalid(&ret, "rstb_dpi_real");
+            0.0
+        }
+    }
+}
+
+/// # Safety
+/// `arg` and `ret` must hold `width` bits, as passed by the simulator.
+#[no_mangle]
+pub unsafe extern "C" fn rstb_dpi_bits(
+    name: *const c_char,
+    width: i32,
+    arg: *const SvBitVecVal,
+    ret: *mut SvBitVecVal,
+) {
+    let width = width.max(1) as usize;
+    let words: Vec<VecWord> = std::slice::from_raw_parts(arg, width.div_ceil(32))
+        .iter()
+        .map(|aval| VecWord {
+            aval: *aval,
+            bval: 0,
+        })
+        .collect();
+    let ret_val = call(name, vec![from_words(&words, width, false)]);
+    if ret_val == Val::None {
+        return;
+    }
+    let ret = std::slice::from_raw_parts_mut(ret, width.div_ceil(32));
+    let words = to_words(&ret_val, width).unwrap_or_else(|| {
+        log_invalid(&ret_val, "rstb_dpi_bits");
+        vec![VecWord::default(); ret.len()]
+    });
+    // X and Z are returned as 0
+    for (r, w) in ret.iter_mut().zip(words) {
+        *r = w.aval & !w.bval;
+    }
+}
+
+/// # Safety
+/// `arg` and `ret` must hold `width` bits, as passed by the simulator.
+#[no_mangle]
+pub unsafe extern "C" fn rstb_dpi_logic(
+    name: *const c_char,
+    width: i32,
+    arg: *const SvLogicVecVal,
+    ret: *mut SvLogicVecVal,
+) {
+    let width = width.max(1) as usize;
+    let words: Vec<VecWord> = std::slice::from_raw_parts(arg, width.div_ceil(32))
+        .iter()
+        .map(|w| VecWord {
+            aval: w.aval,
+            bval: w.bval,
+        })
+        .collect();
+    let ret_val = call(name, vec![from_words(&words, width, true)]);
+    if ret_val == Val::None {
+        return;
+    }
+    let ret = std::slice::from_raw_parts_mut(ret, width.div_ceil(32));
+    let words = to_words(&ret_val, width).unwrap_or_else(|| {
+        log_invalid(&ret_val, "rstb_dpi_logic");
+        bin_to_vec(&"x".repeat(width))
+    });
+    for (r, w) in ret.iter_mut().zip(words) {
+        r.aval = w.aval;
+        r.bval = w.bval;
+    }
+}
+
+// Two-state vectors of up to 32 bits are passed to handlers as `Val::Int`, all others as
+// `Val::Logic`.
+fn from_words(words: &[VecWord], width: usize, four_state: bool) -> Val {
+    if !four_state && width <= 32 {
+        let mask = u32::MAX >> (32 - width);
+        return Val::Int(words[0].aval & mask);
+    }
+    Val::Logic(vec_to_bin(words, width).parse().unwrap())
+}
+
+// Words of a handler's return value, truncated or zero extended to `width` bits. `None` for
+// values which aren't vectors.
+fn to_words(val: &Val, width: usize) -> Option<Vec<VecWord>> {
+    let mut words = match val {
+        Val::Int(v) => vec![VecWord { aval: *v, bval: 0 }],
+        Val::Signed(v) => vec![VecWord {
+            aval: *v as u32,
+            bval: 0,
+        }],
+        Val::BitStr(bin) => bin_to_vec(bin),
+        Val::Logic(l) => bin_to_vec(&l.to_string()),
+        _ => return None,
+    };
+    words.resize(width.div_ceil(32), VecWord::default());
+    if !width.is_multiple_of(32) {
+        let last = words.last_mut().unwrap();
+        last.aval &= (1 << (width % 32)) - 1;
+        last.bval &= (1 << (width % 32)) - 1;
+    }
+    Some(words)
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::ffi::CString;
+
+    fn name(name: &str) -> CString {
+        CString::new(name).unwrap()
+    }
+
+    #[test]
+    fn calls_from_sv() {
+        register_dpi("dpi_inc", |args| match args {
+            [Val::Signed(v)] => Val::Signed(v + 1),
+            [Val::Int(v)] => Val::Int(v + 1),
+            [Val::Logic(l)] => Val::Logic(l.slice(l.width() - 1, 8).concat(&LogicVec::zeros(8))),
+            _ => Val::Float(2.5),
+        });
+        let r = MockSim::new("top").run(|_| {
+            async move {
+                let call = Task::spawn(async { Ok(Trigger::dpi("dpi_inc").await) });
+                Trigger::timer(1, "ns").await;
+                let inc = name("dpi_inc");
+                assert_eq!(rstb_dpi_int(inc.as_ptr(), -2), -1);
+                assert_eq!(call.await, Ok(Val::Vec(vec![Val::Signed(-2)])));
+                assert_eq!(rstb_dpi_real(inc.as_ptr(), 1.0), 2.5);
+                let mut ret = [0u32; 2];
+                unsafe {
+                    rstb_dpi_bits(
+                        inc.as_ptr(),
+                        12,
+                        [0xffff_f00a, 0].as_ptr(),
+                        ret.as_mut_ptr(),
+                    )
+                };
+                assert_eq!(ret[0], 0xb);
+                // wider vectors are passed as Val::Logic
+                let arg = [
+                    SvLogicVecVal {
+                        aval: 0x1234_5678,
+                        bval: 0x0000_00f0,
+                    },
+                    SvLogicVecVal {
+                        aval: 0xff,
+                        bval: 0,
+                    },
+                ];
+                let mut ret = [SvLogicVecVal::default(); 2];
+                unsafe { rstb_dpi_logic(inc.as_ptr(), 40, arg.as_ptr(), ret.as_mut_ptr()) };
+                assert_eq!((ret[0].aval, ret[0].bval), (0x1234_5600, 0));
+                assert_eq!((ret[1].aval, ret[1].bval), (0xff, 0));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn invalid_calls_return_zero_or_x() {
+        register_dpi("dpi_string", |_| Val::String("abc".to_string()));
+        let r = MockSim::new("top").run(|_| {
+            async move {
+                let (string, unknown) = (name("dpi_string"), name("dpi_unknown"));
+                assert_eq!(rstb_dpi_int(string.as_ptr(), 1), 0);
+                assert_eq!(rstb_dpi_int(unknown.as_ptr(), 1), 0);
+                assert_eq!(rstb_dpi_real(string.as_ptr(), 1.0), 0.0);
+                let mut ret = [1u32; 2];
+                unsafe { rstb_dpi_bits(string.as_ptr(), 40, [0, 0].as_ptr(), ret.as_mut_ptr()) };
+                assert_eq!(ret, [0, 0]);
+                let arg = [SvLogicVecVal::default(); 2];
+                let mut ret = [SvLogicVecVal::default(); 2];
+                unsafe { rstb_dpi_logic(string.as_ptr(), 40, arg.as_ptr(), ret.as_mut_ptr()) };
+                assert_eq!((ret[0].aval, ret[0].bval), (u32::MAX, u32::MAX));
+                assert_eq!((ret[1].aval, ret[1].bval), (0xff, 0xff));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
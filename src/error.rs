@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::sim_if::ObjectKind;
+
+pub type SimResult<T> = Result<T, RstbError>;
+
+/// Error of a simulator access
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RstbError {
+    /// There is no object with this full name
+    NotFound { name: String },
+    /// `op` can't be used on objects of this kind
+    Kind {
+        op: &'static str,
+        object: String,
+        kind: ObjectKind,
+    },
+    /// `value` doesn't fit the object, e.g. a binary string of the wrong length
+    InvalidValue {
+        op: &'static str,
+        object: String,
+        value: String,
+    },
+    /// Values can't be written in the ReadOnly synchronization region
+    ReadOnly { object: String },
+    /// The simulator failed `op`. `message` is the simulator's error message, if it reported one.
+    Sim {
+        op: &'static str,
+        object: String,
+        message: String,
+    },
+}
+
+impl RstbError {
+    pub(crate) fn sim(
+        op: &'static str,
+        object: impl Into<String>,
+        message: Option<String>,
+    ) -> Self {
+        RstbError::Sim {
+            op,
+            object: object.into(),
+            message: message.unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for RstbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RstbError::NotFound { name } => write!(f, "Could not get object with name {}", name),
+            RstbError::Kind { op, object, kind } => {
+                write!(f, "Can't use {}() on {} of kind {:?}", op, object, kind)
+            }
+            RstbError::InvalidValue { op, object, value } => {
+                write!(f, "Can't set {} to {} using {}()", object, value, op)
+            }
+            RstbError::ReadOnly { object } => write!(
+                f,
+                "Can't write {} in the ReadOnly synchronization region. Await a timer or edge first.",
+                object
+            ),
+            RstbError::Sim {
+                op,
+                object,
+                message,
+            } => {
+                write!(f, "Simulator failed {}", op)?;
+                if !object.is_empty() {
+                    write!(f, " on {}", object)?;
+                }
+                if !message.is_empty() {
+                    write!(f, ": {}", message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RstbError {}
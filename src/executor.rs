@@ -16,6 +16,7 @@ lazy_mut! {
 }
 
 
+#[allow(static_mut_refs)]
 pub(crate) fn schedule_task(task: Arc<Task>) {
     unsafe {
         READY_QUEUE.add(task).expect("Error queueing task.");
@@ -27,6 +28,7 @@ pub(crate) fn clear_ready_queue() {
 
 
 #[inline]
+#[allow(static_mut_refs)]
 pub(crate) fn run_once() {
     while let Ok(task) = unsafe { READY_QUEUE.remove() } {
         process_task(task);
@@ -37,17 +39,23 @@ pub(crate) fn run_once() {
 #[inline]
 fn process_task(task: Arc<Task>) {
     if *task.state.lock().unwrap() == TaskState::Cancelled {
-        // do not execute if state is cancelled, will be dropped once all references disappear
+        // do not execute if state is cancelled
+        let fut = task.future.lock().unwrap().take();
+        drop(fut);
         return
     }
 
     let mut fut_slot = task.future.lock().unwrap();
     if let Some(mut fut) = fut_slot.take() {
         let waker = waker_ref(&task);
-        let context = &mut Context::from_waker(&*waker);
+        let context = &mut Context::from_waker(&waker);
         let result = match fut.as_mut().poll(context) {
             Poll::Pending => {
-                *fut_slot = Some(fut);
+                // a task which cancelled itself, e.g. by failing the test, drops its future
+                // right away, so its triggers deregister
+                if *task.state.lock().unwrap() != TaskState::Cancelled {
+                    *fut_slot = Some(fut);
+                }
                 None
             }
             Poll::Ready(result) => Some(result),
@@ -112,9 +120,14 @@ impl Task {
         }
     }
     pub fn cancel(&self) {
-        // since we can't know the tasks current trigger, we set its state
-        // to cancelled so it will be discarded once the trigger fires
         *self.state.lock().unwrap() = TaskState::Cancelled;
+        // dropping the future deregisters its triggers. A task cancelling itself is being
+        // polled, its future is dropped once the poll returns.
+        let fut = match self.future.try_lock() {
+            Ok(mut fut_slot) => fut_slot.take(),
+            Err(_) => None,
+        };
+        drop(fut);
         let mut tx_slot = self.join_tx.lock().unwrap();
         let tx = tx_slot.take().unwrap();
         let _ = tx.send(Ok(Val::None));
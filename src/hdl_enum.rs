@@ -0,0 +1,16 @@
+pub use librstb_derive::HdlEnum;
+
+/// Rust enum which mirrors an HDL enumeration type, e.g. an FSM state, for use with
+/// `SimObject::enum_as()` and `SimObject::set_enum()`. Usually derived with
+/// `#[derive(HdlEnum)]`.
+pub trait HdlEnum: Sized {
+    /// Name of the HDL literal
+    fn literal(&self) -> &'static str;
+    /// Variant for an HDL literal, compared ignoring case and underscores
+    fn from_literal(literal: &str) -> Option<Self>;
+}
+
+// VHDL identifiers are case insensitive, and Rust and HDL naming conventions differ.
+pub(crate) fn same_literal(a: &str, b: &str) -> bool {
+    a.replace('_', "").eq_ignore_ascii_case(&b.replace('_', ""))
+}
@@ -6,9 +6,10 @@ pub(crate) fn create_junit_xml() {
     let mut test_cases = Vec::new();
 
     for t in tests.iter().map(|obj| obj.get()) {
-        let tc = match t.result.as_ref().unwrap() {
-            Ok(_) => TestCaseBuilder::success(&t.name, Duration::seconds_f64(t.time_secs)),
-            Err(e) => TestCaseBuilder::failure(
+        let tc = match t.result.as_ref() {
+            None => TestCaseBuilder::skipped(&t.name),
+            Some(Ok(_)) => TestCaseBuilder::success(&t.name, Duration::seconds_f64(t.time_secs)),
+            Some(Err(e)) => TestCaseBuilder::failure(
                 &t.name,
                 Duration::seconds_f64(t.time_secs),
                 "failure",
@@ -17,6 +18,17 @@ pub(crate) fn create_junit_xml() {
         }.build();
         test_cases.push(tc);
     }
+    for name in test::UNKNOWN_TESTS.get().into_iter().flatten() {
+        test_cases.push(
+            TestCaseBuilder::failure(
+                name,
+                Duration::ZERO,
+                "failure",
+                &format!("TESTCASE {} does not exist", name),
+            )
+            .build(),
+        );
+    }
 
     let test_suite = TestSuiteBuilder::new(crate::CRATE_NAME.get().unwrap())
         .add_testcases(test_cases)
@@ -1,21 +1,38 @@
+// lets derive macros, which refer to `::librstb`, be used within this crate
+extern crate self as librstb;
+
 mod assertion;
+#[cfg(feature = "dpi")]
+pub mod dpi;
+mod error;
 mod executor;
+pub mod hdl_enum;
 mod junit;
+mod logic;
+pub mod memfile;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod prelude;
+mod quirks;
 mod rstb_obj;
 mod seamap;
 mod signal;
 pub mod sim_if;
+#[cfg_attr(not(any(feature = "vpi", feature = "mock")), allow(dead_code))]
+mod systf;
 mod test;
 pub mod testbench;
 mod trigger;
 pub mod utils;
 mod value;
 #[cfg(feature = "vhpi")]
+// the mock replaces all simulator interfaces
+#[cfg_attr(feature = "mock", allow(dead_code))]
 mod vhpi;
 #[cfg(feature = "vhpi")]
 #[allow(
     non_upper_case_globals,
+    non_snake_case,
     dead_code,
     non_camel_case_types,
     clippy::upper_case_acronyms
@@ -52,12 +69,13 @@ use lazy_static::lazy_static;
 use once_cell::sync::OnceCell;
 use rstb_obj::RstbObjSafe;
 use sim_if::SIM_IF;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time;
 use value::Val;
 // use test::{RstbTests, Test};
 
-pub type SimpleResult<T> = Result<T, ()>;
+pub use error::{RstbError, SimResult};
 pub type RstbResult = Result<Val, Val>;
 
 static SIM_START_TIME: OnceCell<time::Instant> = OnceCell::new();
@@ -70,10 +88,18 @@ lazy_static! {
         RstbObjSafe::new(None);
 }
 pub static CRATE_NAME: OnceCell<String> = OnceCell::new();
+static FINISH_AFTER_TESTS: AtomicBool = AtomicBool::new(false);
+
+/// Finish the simulation once the last test has completed, instead of waiting for the HDL to
+/// call `$finish`. Can be enabled from any test before the last one completes.
+pub fn finish_after_tests(enable: bool) {
+    FINISH_AFTER_TESTS.store(enable, Ordering::Relaxed);
+}
 
+#[cfg(feature = "vpi")]
 #[macro_export]
 macro_rules! run_with_vpi {
-    ($( $i:ident ),+) => {
+    ($( $i:ident ),+ $(; setup = $setup:path)?) => {
         #[allow(non_upper_case_globals)]
         #[no_mangle]
         pub static vlog_startup_routines: [Option<extern "C" fn()>; 2] =
@@ -83,7 +109,9 @@ macro_rules! run_with_vpi {
         #[allow(clippy::vec_init_then_push)]
         #[no_mangle]
         pub extern "C" fn vpi_entry_point() {
-            CRATE_NAME.set(std::module_path!().to_string()).unwrap();
+            let _ = CRATE_NAME.set(std::module_path!().to_string());
+            // e.g. register system tasks, which is only possible while rstb is loaded
+            $($setup();)?
             // add tests to execution vector
             let mut tests = RstbTests::new();
             $(tests.push(Test::new(stringify!($i).to_string(), |sim_root| { $i(sim_root).boxed() }));)+
@@ -92,6 +120,31 @@ macro_rules! run_with_vpi {
         }
     }
 }
+
+#[cfg(feature = "vhpi")]
+#[macro_export]
+macro_rules! run_with_vhpi {
+    ($( $i:ident ),+ $(; setup = $setup:path)?) => {
+        #[allow(non_upper_case_globals)]
+        #[no_mangle]
+        pub static vhpi_startup_routines: [Option<extern "C" fn()>; 2] =
+            [Some(vhpi_entry_point), None];
+
+
+        #[allow(clippy::vec_init_then_push)]
+        #[no_mangle]
+        pub extern "C" fn vhpi_entry_point() {
+            let _ = CRATE_NAME.set(std::module_path!().to_string());
+            // e.g. register system tasks, which is only possible while rstb is loaded
+            $($setup();)?
+            // add tests to execution vector
+            let mut tests = RstbTests::new();
+            $(tests.push(Test::new(stringify!($i).to_string(), |sim_root| { $i(sim_root).boxed() }));)+
+
+            vhpi_init(tests);
+        }
+    }
+}
 pub fn pass_test(msg: &str) {
     // Passes test that has not already failed/passed
     if let Some((task, test)) = CURRENT_TEST.get().take() {
@@ -113,6 +166,7 @@ fn tear_down_test(test: Arc<Task>) {
     trigger::cancel_all_triggers();
     executor::clear_ready_queue();
     rstb_obj::clear_objects();
+    signal::clear_pending_writes();
     test.cancel();
 }
 
@@ -120,12 +174,44 @@ fn start_of_simulation() {
     // start timer
     SIM_START_TIME.set(time::Instant::now()).unwrap();
 
+    quirks::log_simulator();
+    let unknown = schedule_tests(test::TESTS.get().unwrap());
+    let _ = test::UNKNOWN_TESTS.set(unknown);
+
+    // execute first simulation tick
+    executor::run_once();
+}
+
+// Schedules the tests selected with +TESTCASE=a,b, or all of them. Returns the selected names
+// without a test.
+pub(crate) fn schedule_tests(tests: &'static test::RstbTests) -> Vec<String> {
     let sim_root = signal::SimObject::get_root().unwrap();
 
+    let selected: Option<Vec<String>> = SIM_IF
+        .plusarg("TESTCASE")
+        .map(|names| names.split(',').map(|n| n.trim().to_string()).collect());
+    let mut unknown = Vec::new();
+    if let Some(names) = &selected {
+        unknown = names
+            .iter()
+            .filter(|name| !tests.iter().any(|t| &&t.get().name == name))
+            .cloned()
+            .collect();
+        for name in &unknown {
+            SIM_IF.log(&format!("TESTCASE {} does not exist", name));
+        }
+    }
+
     // All tests are scheduled in a chain at simulation start up by awaiting the previous test completion.
     // Wrapping logic handles test results, timers, etc.
     let mut join_handle = None;
-    for test in test::TESTS.get().unwrap().iter() {
+    for test in tests.iter() {
+        if let Some(names) = &selected {
+            if !names.contains(&test.get().name) {
+                test.with_mut(|mut t| t.skipped = true);
+                continue;
+            }
+        }
         join_handle = Some(executor::Task::spawn_from_future(async move {
             // await previous test, if there is one
             if let Some(handle) = join_handle {
@@ -161,9 +247,27 @@ fn start_of_simulation() {
             Ok(Val::None)
         }));
     }
+    match join_handle {
+        Some(last) => {
+            executor::Task::spawn_from_future(async move {
+                let _ = last.await;
+                finish_if_requested();
+                Ok(Val::None)
+            });
+        }
+        // no test was selected
+        None => finish_if_requested(),
+    }
+    unknown
+}
 
-    // execute first simulation tick
-    executor::run_once();
+fn finish_if_requested() {
+    if FINISH_AFTER_TESTS.load(Ordering::Relaxed) {
+        SIM_IF.log("All tests completed, finishing simulation");
+        if let Err(e) = SIM_IF.control(sim_if::SimControl::Finish) {
+            SIM_IF.log(&format!("Could not finish simulation: {}", e));
+        }
+    }
 }
 
 fn end_of_simulation() {
@@ -173,12 +277,18 @@ fn end_of_simulation() {
 
     // TODO: use prettytable
     for test in test::TESTS.get().unwrap().iter() {
-        let (name, result_str, time, sim_time) = test.with_mut(|t| {
+        let (name, result_str, time, sim_time) = test.with_mut(|mut t| {
+            if t.result.is_none() && !t.skipped {
+                t.set_result(Err(Val::String(
+                    "Simulation ended before test completed.".to_string(),
+                )));
+            }
             (
                 t.name.clone(),
-                match t.result.as_ref().unwrap() {
-                    Ok(_) => "passed",
-                    _ => "failed",
+                match t.result.as_ref() {
+                    Some(Ok(_)) => "passed",
+                    Some(Err(_)) => "failed",
+                    None => "skipped",
                 },
                 t.time_secs,
                 t.sim_time_ns,
@@ -190,6 +300,12 @@ fn end_of_simulation() {
             name, result_str, time, sim_time, sim_speed
         ));
     }
+    for name in test::UNKNOWN_TESTS.get().into_iter().flatten() {
+        SIM_IF.log(&format!(
+            "TEST {}: Result=failed, TESTCASE does not exist",
+            name
+        ));
+    }
     SIM_IF.log("TOTAL SIMULATION");
     SIM_IF.log(&format!("Simulation time: {} ns", final_sim_time));
     SIM_IF.log(&format!("Real time: {:.3} s", duration));
@@ -204,9 +320,15 @@ fn end_of_simulation() {
  *  VPI
  */
 
+#[cfg(feature = "vpi")]
 pub fn vpi_init(tests: test::RstbTests) {
-    // set tests to execute
-    test::TESTS.set(tests).unwrap();
+    // system tasks must be registered while VPI loads rstb, even if VHPI was first
+    systf::register_all();
+    // set tests to execute, unless rstb has already been loaded through VHPI
+    if test::TESTS.set(tests).is_err() {
+        return;
+    }
+    sim_if::set_primary_interface(sim_if::Interface::Vpi);
 
     unsafe {
         let mut cb_data = vpi_user::t_cb_data {
@@ -226,12 +348,14 @@ pub fn vpi_init(tests: test::RstbTests) {
     };
 }
 
+#[cfg(feature = "vpi")]
 #[no_mangle]
 extern "C" fn vpi_start_of_simulation(_cb_data: *mut vpi_user::t_cb_data) -> vpi_user::PLI_INT32 {
     start_of_simulation();
     0
 }
 
+#[cfg(feature = "vpi")]
 #[no_mangle]
 extern "C" fn vpi_end_of_simulation(_cb_data: *mut vpi_user::t_cb_data) -> vpi_user::PLI_INT32 {
     end_of_simulation();
@@ -243,22 +367,25 @@ extern "C" fn vpi_end_of_simulation(_cb_data: *mut vpi_user::t_cb_data) -> vpi_u
  */
 
 #[cfg(feature = "vhpi")]
-#[allow(clippy::missing_safety_doc)]
 #[no_mangle]
 extern "C" fn vhpi_start_of_simulation(_cb_data: *const vhpi_user::vhpiCbDataT) {
-    eprintln!("vhpi_start_of_simulation");
     start_of_simulation();
 }
 
 #[cfg(feature = "vhpi")]
-#[allow(clippy::missing_safety_doc)]
 #[no_mangle]
 extern "C" fn vhpi_end_of_simulation(_cb_data: *const vhpi_user::vhpiCbDataT) {
     end_of_simulation();
 }
 
 #[cfg(feature = "vhpi")]
-pub fn vhpi_init() {
+pub fn vhpi_init(tests: test::RstbTests) {
+    // set tests to execute, unless rstb has already been loaded through VPI
+    if test::TESTS.set(tests).is_err() {
+        return;
+    }
+    sim_if::set_primary_interface(sim_if::Interface::Vhpi);
+
     unsafe {
         let mut cb_data = vhpi_user::vhpiCbDataT {
             reason: vhpi_user::vhpiCbStartOfSimulation as i32,
@@ -275,8 +402,64 @@ pub fn vhpi_init() {
     };
 }
 
-#[cfg(feature = "vhpi")]
-#[no_mangle]
-pub extern "C" fn vhpi_entry_point() {
-    vhpi_init();
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use crate::prelude::*;
+    use futures::future::BoxFuture;
+
+    fn slow(_: SimObject) -> BoxFuture<'static, RstbResult> {
+        async {
+            Trigger::timer(10, "ns").await;
+            Ok(Val::None)
+        }
+        .boxed()
+    }
+
+    fn finishing(_: SimObject) -> BoxFuture<'static, RstbResult> {
+        async {
+            finish_after_tests(true);
+            Trigger::timer(5, "ns").await;
+            Err(Val::String("failed".to_string()))
+        }
+        .boxed()
+    }
+
+    fn tests() -> RstbTests {
+        let mut tests = RstbTests::new();
+        tests.push(Test::new("slow".to_string(), slow));
+        tests.push(Test::new("finishing".to_string(), finishing));
+        tests
+    }
+
+    fn failed() -> Option<RstbResult> {
+        Some(Err(Val::String("String(\"failed\")".to_string())))
+    }
+
+    #[test]
+    fn tests_run_in_order() {
+        let run = MockSim::new("top").run_tests(tests());
+        assert_eq!(run.results, [Some(Ok(Val::String("None".to_string()))), failed()]);
+        assert!(run.unknown_tests.is_empty());
+        assert!(run.finished);
+        let mut tests = RstbTests::new();
+        tests.push(Test::new("slow".to_string(), slow));
+        let run = MockSim::new("top").run_tests(tests);
+        assert!(!run.finished);
+    }
+
+    #[test]
+    fn testcase_selects_tests() {
+        let sim = MockSim::new("top").args(&["sim", "+TESTCASE=finishing, missing"]);
+        let run = sim.run_tests(tests());
+        assert_eq!(run.results, [None, failed()]);
+        assert_eq!(run.unknown_tests, ["missing"]);
+    }
+
+    #[test]
+    fn finish_without_selected_test() {
+        let sim = MockSim::new("top").args(&["+TESTCASE=missing"]);
+        let run = sim.finish_after_tests().run_tests(tests());
+        assert_eq!(run.results, [None, None]);
+        assert!(run.finished);
+    }
 }
@@ -0,0 +1,548 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Index, IndexMut, Not};
+use std::str::FromStr;
+
+/// Single logic value. The 4-state Verilog values are `Zero`, `One`, `X` and `Z`, VHDL's
+/// std_logic additionally has `U`, `W`, `L`, `H` and `DontCare` ('-').
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Logic {
+    U,
+    X,
+    Zero,
+    One,
+    Z,
+    W,
+    L,
+    H,
+    DontCare,
+}
+
+impl Logic {
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c.to_ascii_uppercase() {
+            'U' => Logic::U,
+            'X' => Logic::X,
+            '0' => Logic::Zero,
+            '1' => Logic::One,
+            'Z' => Logic::Z,
+            'W' => Logic::W,
+            'L' => Logic::L,
+            'H' => Logic::H,
+            '-' => Logic::DontCare,
+            _ => return None,
+        })
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Logic::U => 'U',
+            Logic::X => 'X',
+            Logic::Zero => '0',
+            Logic::One => '1',
+            Logic::Z => 'Z',
+            Logic::W => 'W',
+            Logic::L => 'L',
+            Logic::H => 'H',
+            Logic::DontCare => '-',
+        }
+    }
+
+    /// True for values which resolve to 0 or 1 (including weak 'L' and 'H').
+    pub fn is_resolvable(self) -> bool {
+        self.to_bool().is_some()
+    }
+
+    pub fn to_bool(self) -> Option<bool> {
+        match self {
+            Logic::Zero | Logic::L => Some(false),
+            Logic::One | Logic::H => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Maps to the 4-state values, as seen through VPI.
+    pub fn to_x01z(self) -> Self {
+        match self {
+            Logic::Zero | Logic::L => Logic::Zero,
+            Logic::One | Logic::H => Logic::One,
+            Logic::Z => Logic::Z,
+            _ => Logic::X,
+        }
+    }
+}
+
+impl From<bool> for Logic {
+    fn from(b: bool) -> Self {
+        match b {
+            true => Logic::One,
+            false => Logic::Zero,
+        }
+    }
+}
+
+impl fmt::Display for Logic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// Operators follow the std_logic_1164 tables: 'U' propagates, other unresolvable values give 'X'.
+impl BitAnd for Logic {
+    type Output = Logic;
+    fn bitand(self, rhs: Logic) -> Logic {
+        match (self.to_bool(), rhs.to_bool()) {
+            (Some(false), _) | (_, Some(false)) => Logic::Zero,
+            _ if self == Logic::U || rhs == Logic::U => Logic::U,
+            (Some(true), Some(true)) => Logic::One,
+            _ => Logic::X,
+        }
+    }
+}
+
+impl BitOr for Logic {
+    type Output = Logic;
+    fn bitor(self, rhs: Logic) -> Logic {
+        match (self.to_bool(), rhs.to_bool()) {
+            (Some(true), _) | (_, Some(true)) => Logic::One,
+            _ if self == Logic::U || rhs == Logic::U => Logic::U,
+            (Some(false), Some(false)) => Logic::Zero,
+            _ => Logic::X,
+        }
+    }
+}
+
+impl BitXor for Logic {
+    type Output = Logic;
+    fn bitxor(self, rhs: Logic) -> Logic {
+        match (self.to_bool(), rhs.to_bool()) {
+            _ if self == Logic::U || rhs == Logic::U => Logic::U,
+            (Some(a), Some(b)) => Logic::from(a ^ b),
+            _ => Logic::X,
+        }
+    }
+}
+
+impl Not for Logic {
+    type Output = Logic;
+    fn not(self) -> Logic {
+        match self.to_bool() {
+            Some(b) => Logic::from(!b),
+            None if self == Logic::U => Logic::U,
+            None => Logic::X,
+        }
+    }
+}
+
+/// Vector of logic values. Index 0 is the least significant bit, while strings are written
+/// MSB first like in HDL code.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LogicVec {
+    bits: Vec<Logic>,
+}
+
+impl LogicVec {
+    pub fn new(width: usize, value: Logic) -> Self {
+        LogicVec {
+            bits: vec![value; width],
+        }
+    }
+
+    pub fn zeros(width: usize) -> Self {
+        LogicVec::new(width, Logic::Zero)
+    }
+
+    /// Takes the `width` least significant bits of `val`. Bits above 64 are sign extended.
+    pub fn from_i64(val: i64, width: usize) -> Self {
+        LogicVec::from_u128(val as i128 as u128, width)
+    }
+
+    pub fn from_u64(val: u64, width: usize) -> Self {
+        LogicVec::from_u128(val as u128, width)
+    }
+
+    pub fn from_u128(val: u128, width: usize) -> Self {
+        LogicVec {
+            bits: (0..width)
+                .map(|i| Logic::from(i < 128 && (val >> i) & 1 == 1))
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<Logic> {
+        self.bits.get(i).copied()
+    }
+
+    pub fn set(&mut self, i: usize, val: Logic) {
+        self.bits[i] = val;
+    }
+
+    /// Bits from LSB to MSB
+    pub fn iter(&self) -> impl Iterator<Item = &Logic> {
+        self.bits.iter()
+    }
+
+    /// Bits `hi` down to `lo`, both inclusive, like `vec[hi:lo]` in Verilog.
+    pub fn slice(&self, hi: usize, lo: usize) -> Self {
+        if hi < lo || hi >= self.width() {
+            panic!(
+                "Invalid slice [{}:{}] of LogicVec with width {}.",
+                hi,
+                lo,
+                self.width()
+            );
+        }
+        LogicVec {
+            bits: self.bits[lo..=hi].to_vec(),
+        }
+    }
+
+    /// Concatenation with `self` as upper part, like `{self, lower}` in Verilog.
+    pub fn concat(&self, lower: &LogicVec) -> Self {
+        let mut bits = lower.bits.clone();
+        bits.extend_from_slice(&self.bits);
+        LogicVec { bits }
+    }
+
+    /// True if all bits resolve to 0 or 1.
+    pub fn is_resolvable(&self) -> bool {
+        self.bits.iter().all(|b| b.is_resolvable())
+    }
+
+    pub fn to_x01z(&self) -> Self {
+        LogicVec {
+            bits: self.bits.iter().map(|b| b.to_x01z()).collect(),
+        }
+    }
+
+    /// None if not resolvable or wider than 64 bits.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.width() {
+            0..=64 => self.to_u128().map(|v| v as u64),
+            _ => None,
+        }
+    }
+
+    /// Interprets the value as two's complement. None if not resolvable or wider than 64 bits.
+    pub fn to_i64(&self) -> Option<i64> {
+        let val = self.to_u64()?;
+        let shift = 64 - self.width().max(1) as u32;
+        Some(((val << shift) as i64) >> shift)
+    }
+
+    /// None if not resolvable or wider than 128 bits.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.width() > 128 {
+            return None;
+        }
+        self.bits
+            .iter()
+            .enumerate()
+            .try_fold(0u128, |acc, (i, b)| Some(acc | (b.to_bool()? as u128) << i))
+    }
+
+    fn zip_with(&self, rhs: &LogicVec, op: &str, f: impl Fn(Logic, Logic) -> Logic) -> Self {
+        if self.width() != rhs.width() {
+            panic!(
+                "Width mismatch in LogicVec {}: {} vs. {}",
+                op,
+                self.width(),
+                rhs.width()
+            );
+        }
+        LogicVec {
+            bits: self
+                .bits
+                .iter()
+                .zip(rhs.bits.iter())
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        }
+    }
+}
+
+impl Index<usize> for LogicVec {
+    type Output = Logic;
+    fn index(&self, i: usize) -> &Logic {
+        &self.bits[i]
+    }
+}
+
+impl IndexMut<usize> for LogicVec {
+    fn index_mut(&mut self, i: usize) -> &mut Logic {
+        &mut self.bits[i]
+    }
+}
+
+/// Error parsing a `LogicVec` from a string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLogicError {
+    /// First character which is no logic value
+    pub invalid: char,
+    /// Its character index in the parsed string
+    pub position: usize,
+}
+
+impl fmt::Display for ParseLogicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid logic value '{}' at position {}",
+            self.invalid, self.position
+        )
+    }
+}
+
+impl std::error::Error for ParseLogicError {}
+
+impl FromStr for LogicVec {
+    type Err = ParseLogicError;
+    /// Parses an MSB first string like "01XZ", optionally prefixed by "0b" and with '_' separators.
+    fn from_str(s: &str) -> Result<Self, ParseLogicError> {
+        let prefix = if s.starts_with("0b") { 2 } else { 0 };
+        let mut bits = Vec::with_capacity(s.len());
+        for (position, c) in s.chars().enumerate().skip(prefix) {
+            match Logic::from_char(c) {
+                Some(l) => bits.push(l),
+                None if c == '_' => {}
+                None => {
+                    return Err(ParseLogicError {
+                        invalid: c,
+                        position,
+                    })
+                }
+            }
+        }
+        bits.reverse();
+        Ok(LogicVec { bits })
+    }
+}
+
+impl fmt::Display for LogicVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s: String = self.bits.iter().rev().map(|b| b.to_char()).collect();
+        write!(f, "{}", s)
+    }
+}
+
+impl From<Logic> for LogicVec {
+    fn from(l: Logic) -> Self {
+        LogicVec { bits: vec![l] }
+    }
+}
+
+impl BitAnd for &LogicVec {
+    type Output = LogicVec;
+    fn bitand(self, rhs: &LogicVec) -> LogicVec {
+        self.zip_with(rhs, "and", |a, b| a & b)
+    }
+}
+
+impl BitOr for &LogicVec {
+    type Output = LogicVec;
+    fn bitor(self, rhs: &LogicVec) -> LogicVec {
+        self.zip_with(rhs, "or", |a, b| a | b)
+    }
+}
+
+impl BitXor for &LogicVec {
+    type Output = LogicVec;
+    fn bitxor(self, rhs: &LogicVec) -> LogicVec {
+        self.zip_with(rhs, "xor", |a, b| a ^ b)
+    }
+}
+
+impl Not for &LogicVec {
+    type Output = LogicVec;
+    fn not(self) -> LogicVec {
+        LogicVec {
+            bits: self.bits.iter().map(|b| !*b).collect(),
+        }
+    }
+}
+
+impl BitAnd for LogicVec {
+    type Output = LogicVec;
+    fn bitand(self, rhs: LogicVec) -> LogicVec {
+        &self & &rhs
+    }
+}
+
+impl BitOr for LogicVec {
+    type Output = LogicVec;
+    fn bitor(self, rhs: LogicVec) -> LogicVec {
+        &self | &rhs
+    }
+}
+
+impl BitXor for LogicVec {
+    type Output = LogicVec;
+    fn bitxor(self, rhs: LogicVec) -> LogicVec {
+        &self ^ &rhs
+    }
+}
+
+impl Not for LogicVec {
+    type Output = LogicVec;
+    fn not(self) -> LogicVec {
+        !&self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: &str = "UX01ZWLH-";
+
+    fn vec(s: &str) -> LogicVec {
+        s.parse().unwrap()
+    }
+
+    // Checks `op` against a std_logic_1164 table with rows and columns in the order of VALUES
+    fn check_table(op: fn(Logic, Logic) -> Logic, table: [&str; 9]) {
+        for (a, row) in VALUES.chars().zip(table.iter()) {
+            for (b, exp) in VALUES.chars().zip(row.chars()) {
+                let res = op(Logic::from_char(a).unwrap(), Logic::from_char(b).unwrap());
+                assert_eq!(res.to_char(), exp, "{} op {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_display_round_trip() {
+        for s in [
+            "",
+            "0",
+            "UX01ZWLH-",
+            "1000_0000_0000_0000_0000_0000_0000_0000_0001",
+        ] {
+            assert_eq!(vec(s).to_string(), s.replace('_', ""));
+        }
+        assert_eq!(vec("0b10_zx").to_string(), "10ZX");
+        assert_eq!(vec("10").get(0), Some(Logic::Zero));
+        assert_eq!(vec("10").get(1), Some(Logic::One));
+        assert_eq!(vec("1").get(1), None);
+    }
+
+    #[test]
+    fn parse_error_names_character() {
+        let err = "0b10a1".parse::<LogicVec>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseLogicError {
+                invalid: 'a',
+                position: 4
+            }
+        );
+        assert_eq!(err.to_string(), "Invalid logic value 'a' at position 4");
+        assert_eq!("2".parse::<LogicVec>().unwrap_err().invalid, '2');
+    }
+
+    #[test]
+    fn slice_and_concat() {
+        let v = vec("1100_10XZ");
+        assert_eq!(v.slice(7, 4).to_string(), "1100");
+        assert_eq!(v.slice(1, 0).to_string(), "XZ");
+        assert_eq!(v.slice(3, 3).to_string(), "1");
+        assert_eq!(vec("01").concat(&vec("XZ")).to_string(), "01XZ");
+        assert_eq!(LogicVec::zeros(2).concat(&LogicVec::zeros(0)).width(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid slice [8:0]")]
+    fn slice_out_of_range() {
+        vec("00000000").slice(8, 0);
+    }
+
+    #[test]
+    fn integer_conversions() {
+        assert_eq!(vec("1110").to_i64(), Some(-2));
+        assert_eq!(vec("0110").to_i64(), Some(6));
+        assert_eq!(vec("1").to_i64(), Some(-1));
+        assert_eq!(LogicVec::from_i64(i64::MIN, 64).to_i64(), Some(i64::MIN));
+        assert_eq!(LogicVec::from_i64(-3, 100).to_u128(), Some((1 << 100) - 3));
+        assert_eq!(vec("HL").to_u64(), Some(2));
+        assert_eq!(vec("1X").to_u64(), None);
+        assert_eq!(LogicVec::zeros(65).to_u64(), None);
+        assert_eq!(LogicVec::from_u64(5, 3).to_string(), "101");
+    }
+
+    #[test]
+    fn to_x01z() {
+        assert_eq!(vec("UX01ZWLH-").to_x01z().to_string(), "XX01ZX01X");
+        assert!(vec("01LH").is_resolvable());
+        assert!(!vec("01Z").is_resolvable());
+    }
+
+    #[test]
+    fn operator_tables() {
+        check_table(
+            |a, b| a & b,
+            [
+                "UU0UUU0UU",
+                "UX0XXX0XX",
+                "000000000",
+                "UX01XX01X",
+                "UX0XXX0XX",
+                "UX0XXX0XX",
+                "000000000",
+                "UX01XX01X",
+                "UX0XXX0XX",
+            ],
+        );
+        check_table(
+            |a, b| a | b,
+            [
+                "UUU1UUU1U",
+                "UXX1XXX1X",
+                "UX01XX01X",
+                "111111111",
+                "UXX1XXX1X",
+                "UXX1XXX1X",
+                "UX01XX01X",
+                "111111111",
+                "UXX1XXX1X",
+            ],
+        );
+        check_table(
+            |a, b| a ^ b,
+            [
+                "UUUUUUUUU",
+                "UXXXXXXXX",
+                "UX01XX01X",
+                "UX10XX10X",
+                "UXXXXXXXX",
+                "UXXXXXXXX",
+                "UX01XX01X",
+                "UX10XX10X",
+                "UXXXXXXXX",
+            ],
+        );
+        let not: String = VALUES
+            .chars()
+            .map(|c| (!Logic::from_char(c).unwrap()).to_char())
+            .collect();
+        assert_eq!(not, "UX10XX10X");
+        assert_eq!((&vec("01UX") & &vec("0111")).to_string(), "01UX");
+        assert_eq!((vec("01UX") | vec("0111")).to_string(), "0111");
+        assert_eq!((!vec("01UX")).to_string(), "10UX");
+    }
+
+    #[test]
+    #[should_panic(expected = "Width mismatch in LogicVec and")]
+    fn operator_width_mismatch() {
+        let _ = vec("01") & vec("0");
+    }
+}
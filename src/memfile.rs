@@ -0,0 +1,684 @@
+// Backdoor access to memories: loading and dumping memory instances from/to $readmemh /
+// $readmemb files, Intel HEX and ELF images.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::logic::LogicVec;
+use crate::signal::SimObject;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemFormat {
+    // $readmemh
+    Hex,
+    // $readmemb
+    Bin,
+}
+
+/// Loads a `$readmemh` / `$readmemb` file into `mem`. Addresses (`@addr`) are HDL indices of the
+/// memory, loading starts at the lowest index.
+pub fn load_readmem(mem: SimObject, path: impl AsRef<Path>, format: MemFormat) -> Result<()> {
+    let text = fs::read_to_string(path)?;
+    let (left, right) = mem.range();
+    let (low, high) = (left.min(right) as i64, left.max(right) as i64);
+    let width = mem.index(low as i32).size() as usize;
+    for (addr, word) in parse_readmem(&text, format, width, low)? {
+        if addr < low || addr > high {
+            return Err(invalid(format!(
+                "Address {:#x} out of range of {}",
+                addr,
+                mem.name()
+            )));
+        }
+        mem.index(addr as i32).set_logic(&word);
+    }
+    Ok(())
+}
+
+/// Dumps all words of `mem` in `$readmemh` / `$readmemb` format, one word per line, starting at
+/// the lowest index.
+pub fn dump_readmem(mem: SimObject, path: impl AsRef<Path>, format: MemFormat) -> Result<()> {
+    let (left, right) = mem.range();
+    let mut text = format!("@{:x}\n", left.min(right));
+    for word in mem.read_all() {
+        match format {
+            MemFormat::Hex => text.push_str(&to_hex(&word)),
+            MemFormat::Bin => text.push_str(&word.to_x01z().to_string().to_ascii_lowercase()),
+        }
+        text.push('\n');
+    }
+    fs::write(path, text)
+}
+
+/// Loads an Intel HEX file into `mem`. The byte address `base` maps to the memory's lowest
+/// index, words are little endian.
+pub fn load_ihex(mem: SimObject, path: impl AsRef<Path>, base: u64) -> Result<()> {
+    let text = fs::read_to_string(path)?;
+    write_image(mem, &parse_ihex(&text)?, base)
+}
+
+/// Dumps `mem` as Intel HEX, with the memory's lowest index at byte address `base`. X and Z bits
+/// are dumped as 0.
+pub fn dump_ihex(mem: SimObject, path: impl AsRef<Path>, base: u64) -> Result<()> {
+    let bytes: Vec<u8> = mem.elements().iter().flat_map(|e| e.bytes()).collect();
+    let mut text = String::new();
+    let mut upper = None;
+    let mut addr = base;
+    for chunk in bytes.chunks(16) {
+        if upper != Some(addr >> 16) {
+            upper = Some(addr >> 16);
+            text.push_str(&ihex_record(0x04, 0, &((addr >> 16) as u16).to_be_bytes()));
+        }
+        // records must not cross a 64k boundary
+        let split = (0x1_0000 - (addr & 0xffff) as usize).min(chunk.len());
+        text.push_str(&ihex_record(0x00, addr as u16, &chunk[..split]));
+        if split < chunk.len() {
+            upper = Some((addr >> 16) + 1);
+            text.push_str(&ihex_record(
+                0x04,
+                0,
+                &(((addr >> 16) + 1) as u16).to_be_bytes(),
+            ));
+            text.push_str(&ihex_record(0x00, 0, &chunk[split..]));
+        }
+        addr += chunk.len() as u64;
+    }
+    text.push_str(&ihex_record(0x01, 0, &[]));
+    fs::write(path, text)
+}
+
+/// Loads the loadable segments of an ELF file into `mem`, using their physical addresses. The
+/// byte address `base` maps to the memory's lowest index, words are little endian.
+pub fn load_elf(mem: SimObject, path: impl AsRef<Path>, base: u64) -> Result<()> {
+    let file = fs::read(path)?;
+    let (left, right) = mem.range();
+    let bytes_per_word = (mem.index(left.min(right)).size() as u64).div_ceil(8);
+    let end = base.saturating_add(mem.len() as u64 * bytes_per_word);
+    let mut image = BTreeMap::new();
+    for segment in parse_elf(&file)? {
+        // check before expanding the zero initialized part, memsz comes straight from the file
+        if segment.paddr < base || segment.paddr.saturating_add(segment.memsz) > end {
+            return Err(invalid(format!(
+                "Segment at {:#x} out of range of {}",
+                segment.paddr,
+                mem.name()
+            )));
+        }
+        for (j, b) in segment.data.iter().enumerate() {
+            image.insert(segment.paddr + j as u64, *b);
+        }
+        // zero initialized part, e.g. .bss
+        for j in segment.data.len() as u64..segment.memsz {
+            image.insert(segment.paddr + j, 0);
+        }
+    }
+    write_image(mem, &image, base)
+}
+
+// Writes a byte image to a memory. Words which are only partially covered keep their other bytes.
+fn write_image(mem: SimObject, image: &BTreeMap<u64, u8>, base: u64) -> Result<()> {
+    let (left, right) = mem.range();
+    let low = left.min(right) as u64;
+    let len = mem.len() as u64;
+    let bytes_per_word = (mem.index(low as i32).size() as u64).div_ceil(8);
+    let mut words: BTreeMap<u64, Vec<(usize, u8)>> = BTreeMap::new();
+    for (addr, b) in image {
+        let offset = addr
+            .checked_sub(base)
+            .filter(|o| o / bytes_per_word < len)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "Address {:#x} out of range of {}",
+                    addr,
+                    mem.name()
+                ))
+            })?;
+        words
+            .entry(offset / bytes_per_word)
+            .or_default()
+            .push(((offset % bytes_per_word) as usize, *b));
+    }
+    for (word, bytes) in words {
+        let element = mem.index((low + word) as i32);
+        let mut value = match bytes.len() as u64 == bytes_per_word {
+            true => vec![0; bytes_per_word as usize],
+            false => element.bytes(),
+        };
+        for (i, b) in bytes {
+            value[i] = b;
+        }
+        element.set_bytes(&value);
+    }
+    Ok(())
+}
+
+// Parses a $readmem file into (address, word) pairs, starting at address `start`.
+fn parse_readmem(
+    text: &str,
+    format: MemFormat,
+    width: usize,
+    start: i64,
+) -> Result<Vec<(i64, LogicVec)>> {
+    let mut words = Vec::new();
+    let mut addr = start;
+    for token in tokenize(text) {
+        if let Some(a) = token.strip_prefix('@') {
+            addr = i64::from_str_radix(&a.replace('_', ""), 16)
+                .map_err(|_| invalid(format!("Invalid address {}", token)))?;
+            continue;
+        }
+        words.push((addr, parse_word(&token, format, width)?));
+        addr = addr
+            .checked_add(1)
+            .ok_or_else(|| invalid(format!("Address overflow after {:#x}", addr)))?;
+    }
+    Ok(words)
+}
+
+// Parses Intel HEX records into a byte image.
+fn parse_ihex(text: &str) -> Result<BTreeMap<u64, u8>> {
+    let mut image = BTreeMap::new();
+    let mut upper: u64 = 0;
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = || invalid(format!("Invalid Intel HEX record in line {}", n + 1));
+        let record = line.strip_prefix(':').ok_or_else(err)?;
+        if record.len() % 2 != 0 {
+            return Err(err());
+        }
+        let bytes = (0..record.len() / 2)
+            .map(|i| {
+                record
+                    .get(2 * i..2 * i + 2)
+                    .filter(|b| b.chars().all(|c| c.is_ascii_hexdigit()))
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    .ok_or_else(err)
+            })
+            .collect::<Result<Vec<u8>>>()?;
+        if bytes.len() < 5
+            || bytes.len() != bytes[0] as usize + 5
+            || bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b)) != 0
+        {
+            return Err(err());
+        }
+        let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as u64;
+        let data = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            0x00 => {
+                for (i, b) in data.iter().enumerate() {
+                    image.insert(upper + offset + i as u64, *b);
+                }
+            }
+            0x01 => break,
+            // extended segment address
+            0x02 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u64) << 4,
+            // extended linear address
+            0x04 if data.len() == 2 => {
+                upper = (u16::from_be_bytes([data[0], data[1]]) as u64) << 16
+            }
+            // start addresses
+            0x03 | 0x05 => (),
+            _ => return Err(err()),
+        }
+    }
+    Ok(image)
+}
+
+struct ElfSegment<'a> {
+    paddr: u64,
+    data: &'a [u8],
+    memsz: u64,
+}
+
+// Returns the loadable segments of an ELF file. All header fields are untrusted.
+fn parse_elf(file: &[u8]) -> Result<Vec<ElfSegment<'_>>> {
+    let err = |msg: &str| invalid(format!("Invalid ELF file: {}", msg));
+    if file.len() < 52 || file[0..4] != [0x7f, b'E', b'L', b'F'] {
+        return Err(err("bad magic"));
+    }
+    let is_64 = match file[4] {
+        1 => false,
+        2 => true,
+        _ => return Err(err("unknown class")),
+    };
+    let big_endian = match file[5] {
+        1 => false,
+        2 => true,
+        _ => return Err(err("unknown data encoding")),
+    };
+    let read = |offset: u64, size: usize| -> Result<u64> {
+        let bytes = usize::try_from(offset)
+            .ok()
+            .and_then(|o| file.get(o..o.checked_add(size)?))
+            .ok_or_else(|| err("unexpected end of file"))?;
+        let mut val = 0u64;
+        for i in 0..size {
+            let b = match big_endian {
+                true => bytes[i],
+                false => bytes[size - 1 - i],
+            };
+            val = (val << 8) | b as u64;
+        }
+        Ok(val)
+    };
+    let field = |ph: u64, offset: u64, size: usize| -> Result<u64> {
+        read(
+            ph.checked_add(offset)
+                .ok_or_else(|| err("unexpected end of file"))?,
+            size,
+        )
+    };
+    // offsets of program header fields: type, offset, paddr, filesz, memsz
+    let (ph_off, ph_entsize, ph_num) = match is_64 {
+        true => (read(0x20, 8)?, read(0x36, 2)?, read(0x38, 2)?),
+        false => (read(0x1c, 4)?, read(0x2a, 2)?, read(0x2c, 2)?),
+    };
+    let mut segments = Vec::new();
+    for i in 0..ph_num {
+        let ph = i
+            .checked_mul(ph_entsize)
+            .and_then(|o| o.checked_add(ph_off))
+            .ok_or_else(|| err("unexpected end of file"))?;
+        let (p_type, offset, paddr, filesz, memsz) = match is_64 {
+            true => (
+                field(ph, 0, 4)?,
+                field(ph, 0x08, 8)?,
+                field(ph, 0x18, 8)?,
+                field(ph, 0x20, 8)?,
+                field(ph, 0x28, 8)?,
+            ),
+            false => (
+                field(ph, 0, 4)?,
+                field(ph, 0x04, 4)?,
+                field(ph, 0x0c, 4)?,
+                field(ph, 0x10, 4)?,
+                field(ph, 0x14, 4)?,
+            ),
+        };
+        // PT_LOAD
+        if p_type != 1 {
+            continue;
+        }
+        let data = offset
+            .checked_add(filesz)
+            .and_then(|end| file.get(usize::try_from(offset).ok()?..usize::try_from(end).ok()?))
+            .ok_or_else(|| err("segment exceeds file"))?;
+        if paddr.checked_add(memsz.max(filesz)).is_none() {
+            return Err(err("segment exceeds address space"));
+        }
+        segments.push(ElfSegment {
+            paddr,
+            data,
+            memsz: memsz.max(filesz),
+        });
+    }
+    Ok(segments)
+}
+
+// Splits a $readmem file into values and addresses, dropping comments.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut clean = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix("//") {
+            rest = r.find('\n').map(|i| &r[i..]).unwrap_or("");
+        } else if let Some(r) = rest.strip_prefix("/*") {
+            rest = r.find("*/").map(|i| &r[i + 2..]).unwrap_or("");
+            clean.push(' ');
+        } else {
+            let c = rest.chars().next().unwrap();
+            clean.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    clean.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+fn parse_word(token: &str, format: MemFormat, width: usize) -> Result<LogicVec> {
+    let mut bin = String::new();
+    for c in token.chars().filter(|c| *c != '_') {
+        match (format, c.to_ascii_lowercase()) {
+            (MemFormat::Bin, c @ ('0' | '1' | 'x' | 'z')) => bin.push(c),
+            (MemFormat::Hex, c @ ('x' | 'z')) => bin.push_str(&c.to_string().repeat(4)),
+            (MemFormat::Hex, c) if c.is_ascii_hexdigit() => {
+                bin.push_str(&format!("{:04b}", c.to_digit(16).unwrap()))
+            }
+            _ => return Err(invalid(format!("Invalid value {}", token))),
+        }
+    }
+    // truncate or zero extend to the word width
+    let bin = match bin.len() >= width {
+        true => bin[bin.len() - width..].to_string(),
+        false => format!("{}{}", "0".repeat(width - bin.len()), bin),
+    };
+    bin.parse()
+        .map_err(|_| invalid(format!("Invalid value {}", token)))
+}
+
+fn to_hex(word: &LogicVec) -> String {
+    let bin = word.to_x01z().to_string().to_ascii_lowercase();
+    let padded = format!("{}{}", "0".repeat((4 - bin.len() % 4) % 4), bin);
+    padded
+        .as_bytes()
+        .chunks(4)
+        .map(|nibble| {
+            let nibble = std::str::from_utf8(nibble).unwrap();
+            if nibble.chars().all(|c| c == 'z') {
+                'z'
+            } else if nibble.contains(['x', 'z']) {
+                'x'
+            } else {
+                std::char::from_digit(u32::from_str_radix(nibble, 2).unwrap(), 16).unwrap()
+            }
+        })
+        .collect()
+}
+
+fn ihex_record(kind: u8, addr: u16, data: &[u8]) -> String {
+    let mut bytes = vec![data.len() as u8];
+    bytes.extend_from_slice(&addr.to_be_bytes());
+    bytes.push(kind);
+    bytes.extend_from_slice(data);
+    let checksum = bytes.iter().fold(0u8, |acc, b| acc.wrapping_sub(*b));
+    bytes.push(checksum);
+    let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+    format!(":{}\n", hex)
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str, format: MemFormat, width: usize) -> Vec<(i64, String)> {
+        parse_readmem(text, format, width, 0)
+            .unwrap()
+            .into_iter()
+            .map(|(a, w)| (a, w.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn readmem_hex() {
+        let text = "// header\n@2 a_5 /* skipped\n ff */ x\n@10\n1ff z // trailing";
+        assert_eq!(
+            words(text, MemFormat::Hex, 8),
+            vec![
+                (2, "10100101".to_string()),
+                (3, "0000XXXX".to_string()),
+                (0x10, "11111111".to_string()),
+                (0x11, "0000ZZZZ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn readmem_bin() {
+        assert_eq!(
+            words("1 10x1_z\n@ff 0", MemFormat::Bin, 4),
+            vec![
+                (0, "0001".to_string()),
+                (1, "0X1Z".to_string()),
+                (0xff, "0000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn readmem_errors() {
+        for (text, format) in [
+            ("12 g4", MemFormat::Hex),
+            ("102", MemFormat::Bin),
+            ("@xy 00", MemFormat::Hex),
+            ("@7fffffffffffffff 0 1", MemFormat::Hex),
+        ] {
+            let err = parse_readmem(text, format, 8, 0).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{}", text);
+        }
+    }
+
+    #[test]
+    fn hex_dump_format() {
+        assert_eq!(to_hex(&"1010_0101".parse().unwrap()), "a5");
+        assert_eq!(to_hex(&"1_0000_ZZZZ".parse().unwrap()), "10z");
+        assert_eq!(to_hex(&"HL0X".parse().unwrap()), "x");
+        assert_eq!(to_hex(&"UWZZ".parse().unwrap()), "x");
+    }
+
+    #[test]
+    fn ihex_records() {
+        let text = format!(
+            "{}{}{}{}{}{}",
+            ihex_record(0x00, 0x0010, &[1, 2]),
+            ihex_record(0x04, 0, &[0x00, 0x01]),
+            ihex_record(0x00, 0xfffe, &[3]),
+            ihex_record(0x02, 0, &[0x10, 0x00]),
+            ihex_record(0x05, 0, &[0, 0, 0, 0]),
+            ihex_record(0x00, 0x0004, &[4]),
+        );
+        // anything after the end of file record is ignored
+        let image =
+            parse_ihex(&format!("{}{}:0000000AF6", text, ihex_record(0x01, 0, &[]))).unwrap();
+        let expected: BTreeMap<u64, u8> = [(0x10, 1), (0x11, 2), (0x1_fffe, 3), (0x1_0004, 4)]
+            .into_iter()
+            .collect();
+        assert_eq!(image, expected);
+    }
+
+    #[test]
+    fn ihex_record_format() {
+        assert_eq!(ihex_record(0x01, 0, &[]), ":00000001FF\n");
+        assert_eq!(
+            ihex_record(0x00, 0x0100, &[0x21, 0x46, 0x01]),
+            ":0301000021460194\n"
+        );
+    }
+
+    #[test]
+    fn ihex_errors() {
+        for text in [
+            // bad checksum
+            ":0301000021460195",
+            // length doesn't match byte count
+            ":0401000021460193",
+            ":00000001",
+            // odd number of digits, not hex, not ASCII
+            ":0301000021460194F",
+            ":03010000214601+4",
+            ":030100002146019ä",
+            // no start code, unknown record type
+            "0301000021460194",
+            ":00000006FA",
+        ] {
+            let err = parse_ihex(text).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{}", text);
+        }
+        let err = parse_ihex(":0000000000\n\n:00000001FE").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid Intel HEX record in line 3");
+    }
+
+    // ELF header with one program header right after it
+    pub(super) fn elf32(big_endian: bool, phdr: [u32; 8]) -> Vec<u8> {
+        let half = |v: u16| match big_endian {
+            true => v.to_be_bytes(),
+            false => v.to_le_bytes(),
+        };
+        let word = |v: u32| match big_endian {
+            true => v.to_be_bytes(),
+            false => v.to_le_bytes(),
+        };
+        let mut file = vec![0x7f, b'E', b'L', b'F', 1, if big_endian { 2 } else { 1 }];
+        file.resize(0x1c, 0);
+        file.extend_from_slice(&word(52));
+        file.resize(0x2a, 0);
+        file.extend_from_slice(&half(32));
+        file.extend_from_slice(&half(1));
+        file.resize(52, 0);
+        for v in phdr {
+            file.extend_from_slice(&word(v));
+        }
+        file
+    }
+
+    #[test]
+    fn elf_segments() {
+        for big_endian in [false, true] {
+            // PT_LOAD, offset 0x54, paddr 0x100, filesz 2, memsz 4
+            let mut file = elf32(big_endian, [1, 0x54, 0, 0x100, 2, 4, 0, 0]);
+            file.extend_from_slice(&[0xaa, 0xbb]);
+            let segments = parse_elf(&file).unwrap();
+            assert_eq!(segments.len(), 1);
+            assert_eq!(segments[0].paddr, 0x100);
+            assert_eq!(segments[0].data, &[0xaa, 0xbb]);
+            assert_eq!(segments[0].memsz, 4);
+        }
+        // not PT_LOAD
+        let file = elf32(false, [2, 0x54, 0, 0x100, 2, 4, 0, 0]);
+        assert!(parse_elf(&file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn elf_errors() {
+        let mut bad_class = elf32(false, [1, 0, 0, 0, 0, 0, 0, 0]);
+        bad_class[4] = 3;
+        let mut far_phdr = elf32(false, [1, 0, 0, 0, 0, 0, 0, 0]);
+        far_phdr[0x1c..0x20].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut far_phdr64 = elf32(false, [1, 0, 0, 0, 0, 0, 0, 0]);
+        far_phdr64[4] = 2;
+        far_phdr64[0x20..0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+        far_phdr64[0x38] = 2;
+        for (file, msg) in [
+            (b"\x7fELF".to_vec(), "bad magic"),
+            (bad_class, "unknown class"),
+            (far_phdr, "unexpected end of file"),
+            (far_phdr64, "unexpected end of file"),
+            // segment data past the end of the file
+            (
+                elf32(false, [1, 0x54, 0, 0, 2, 2, 0, 0]),
+                "segment exceeds file",
+            ),
+            (
+                elf32(false, [1, u32::MAX, 0, 0, u32::MAX, 0, 0, 0]),
+                "segment exceeds file",
+            ),
+        ] {
+            let err = parse_elf(&file).err().unwrap();
+            assert_eq!(err.to_string(), format!("Invalid ELF file: {}", msg));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::path::PathBuf;
+
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rstb_memfile_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn readmem_round_trip() {
+        let r = MockSim::new("top").array("mem", 4, 12).run(|dut| {
+            async move {
+                let mem = dut.c("mem");
+                let path = temp("readmem.hex");
+                fs::write(&path, "@1 abc\n@3 x0z").unwrap();
+                load_readmem(mem, &path, MemFormat::Hex).unwrap();
+                Trigger::timer(1, "ns").await;
+                dump_readmem(mem, &path, MemFormat::Hex).unwrap();
+                assert_eq!(
+                    fs::read_to_string(&path).unwrap(),
+                    "@0\nxxx\nabc\nxxx\nx0z\n"
+                );
+                dump_readmem(mem, &path, MemFormat::Bin).unwrap();
+                let bin = fs::read_to_string(&path).unwrap();
+                assert_eq!(bin.lines().nth(4), Some("xxxx0000zzzz"));
+                fs::write(&path, "@4 0").unwrap();
+                let err = load_readmem(mem, &path, MemFormat::Hex).unwrap_err();
+                assert_eq!(err.to_string(), "Address 0x4 out of range of top.mem");
+                fs::remove_file(&path).unwrap();
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn ihex_round_trip() {
+        let r = MockSim::new("top").array("mem", 4, 16).run(|dut| {
+            async move {
+                let mem = dut.c("mem");
+                let path = temp("image.hex");
+                // partial word at the start, full words after it
+                fs::write(
+                    &path,
+                    format!(
+                        "{}{}",
+                        ihex_record(0x00, 0x1001, &[0x12, 0x34, 0x56, 0x78, 0x9a]),
+                        ihex_record(0x01, 0, &[])
+                    ),
+                )
+                .unwrap();
+                mem.write_all(&vec![LogicVec::zeros(16); 4]);
+                Trigger::timer(1, "ns").await;
+                load_ihex(mem, &path, 0x1000).unwrap();
+                Trigger::timer(1, "ns").await;
+                assert_eq!(mem.index(0).u32(), 0x1200);
+                assert_eq!(mem.index(1).u32(), 0x5634);
+                assert_eq!(mem.index(2).u32(), 0x9a78);
+                dump_ihex(mem, &path, 0x1000).unwrap();
+                let dumped = parse_ihex(&fs::read_to_string(&path).unwrap()).unwrap();
+                assert_eq!(dumped.len(), 8);
+                assert_eq!(dumped[&0x1001], 0x12);
+                assert_eq!(dumped[&0x1005], 0x9a);
+                let err = load_ihex(mem, &path, 0x1001).unwrap_err();
+                assert_eq!(err.to_string(), "Address 0x1000 out of range of top.mem");
+                fs::remove_file(&path).unwrap();
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn elf_load() {
+        let r = MockSim::new("top").array("mem", 4, 32).run(|dut| {
+            async move {
+                let mem = dut.c("mem");
+                let path = temp("image.elf");
+                let mut file = tests::elf32(false, [1, 0x54, 0, 0x100, 2, 8, 0, 0]);
+                file.extend_from_slice(&[0xaa, 0xbb]);
+                fs::write(&path, &file).unwrap();
+                mem.write_all(&vec![LogicVec::from_u64(u32::MAX as u64, 32); 4]);
+                Trigger::timer(1, "ns").await;
+                load_elf(mem, &path, 0xfc).unwrap();
+                Trigger::timer(1, "ns").await;
+                assert_eq!(mem.index(0).u32(), 0xffff_ffff);
+                assert_eq!(mem.index(1).u32(), 0xbbaa);
+                assert_eq!(mem.index(2).u32(), 0);
+                // .bss beyond the memory is rejected before it's expanded
+                let mut file = tests::elf32(false, [1, 0x54, 0, 0x100, 2, u32::MAX, 0, 0]);
+                file.extend_from_slice(&[0xaa, 0xbb]);
+                fs::write(&path, &file).unwrap();
+                let err = load_elf(mem, &path, 0xfc).unwrap_err();
+                assert_eq!(err.to_string(), "Segment at 0x100 out of range of top.mem");
+                fs::remove_file(&path).unwrap();
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
@@ -0,0 +1,1142 @@
+// In-process simulator backend. Instead of being loaded by an HDL simulator, the mock
+// models a signal hierarchy and the simulation scheduler itself, so tests can be executed
+// with `cargo test` against a DUT scripted in Rust.
+
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::rstb_obj::RstbObjSafe;
+use crate::signal::SimObject;
+use crate::sim_if::{time_stamp, ObjectKind, PortDirection, SimControl, SimIf, WriteMode, SIM_IF};
+use crate::systf::{self, SysTfKind};
+use crate::test::{RstbTests, Test};
+use crate::trigger::{self, EdgeKind};
+use crate::value::Val;
+use crate::{executor, RstbError, RstbResult, SimResult};
+use futures::future::BoxFuture;
+
+lazy_static! {
+    static ref STATE: Mutex<MockState> = Mutex::new(MockState::default());
+    // Only one mock simulation can run at a time, since rstb's scheduler state is global.
+    static ref RUN_LOCK: Mutex<()> = Mutex::new(());
+}
+
+type Process = Arc<dyn Fn(SimObject) + Send + Sync>;
+
+pub(crate) struct Mock;
+
+#[derive(Default)]
+struct MockState {
+    time: u64,
+    precision: i8,
+    objects: Vec<MockObject>,
+    names: HashMap<String, usize>,
+    root: usize,
+    callbacks: BTreeMap<usize, MockCallback>,
+    next_cb: usize,
+    // value changes which have not yet been reported to callbacks and processes
+    changes: VecDeque<usize>,
+    // writes with zero delay, applied once the current callback returns like simulators do
+    scheduled: Vec<(usize, MockValue)>,
+    processes: Vec<(Vec<usize>, Process)>,
+    // set by SimControl::Stop / Finish
+    finished: bool,
+    args: Vec<String>,
+    // registered system tasks by name
+    systfs: HashMap<String, usize>,
+}
+
+struct MockObject {
+    name: String,
+    kind: ObjectKind,
+    size: i32,
+    signed: bool,
+    direction: Option<PortDirection>,
+    // enumeration literals, encoded by their index
+    literals: Vec<String>,
+    driven: MockValue,
+    forced: Option<MockValue>,
+}
+
+#[derive(Clone, PartialEq)]
+enum MockValue {
+    // binary string, MSB first
+    Bits(String),
+    Real(f64),
+    Str(String),
+}
+
+impl MockObject {
+    fn value(&self) -> &MockValue {
+        self.forced.as_ref().unwrap_or(&self.driven)
+    }
+    fn bits(&self, op: &'static str) -> SimResult<&str> {
+        match self.value() {
+            MockValue::Bits(bits) => Ok(bits),
+            _ => Err(self.kind_error(op)),
+        }
+    }
+    fn kind_error(&self, op: &'static str) -> RstbError {
+        RstbError::Kind {
+            op,
+            object: self.name.clone(),
+            kind: self.kind,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MockCallback {
+    Time(u64),
+    Edge(usize),
+    ReadWrite,
+    ReadOnly,
+    NextTimeStep,
+    StartOfTimeStep(u64),
+    Nba,
+}
+
+fn invalid_handle(hdl: usize) -> RstbError {
+    RstbError::NotFound {
+        name: format!("handle {:#x}", hdl),
+    }
+}
+
+#[inline]
+fn state() -> MutexGuard<'static, MockState> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl MockState {
+    fn object(&self, hdl: usize) -> SimResult<&MockObject> {
+        hdl.checked_sub(1)
+            .and_then(|i| self.objects.get(i))
+            .ok_or_else(|| invalid_handle(hdl))
+    }
+    fn object_mut(&mut self, hdl: usize) -> SimResult<&mut MockObject> {
+        hdl.checked_sub(1)
+            .and_then(|i| self.objects.get_mut(i))
+            .ok_or_else(|| invalid_handle(hdl))
+    }
+    fn write(
+        &mut self,
+        op: &'static str,
+        hdl: usize,
+        value: MockValue,
+        mode: WriteMode,
+    ) -> SimResult<()> {
+        let obj = self.object_mut(hdl)?;
+        let kind_ok = match &value {
+            MockValue::Bits(_) => obj.kind == ObjectKind::Bits,
+            MockValue::Real(_) => obj.kind == ObjectKind::Real,
+            MockValue::Str(_) => obj.kind == ObjectKind::String,
+        };
+        if !kind_ok {
+            return Err(obj.kind_error(op));
+        }
+        if let MockValue::Bits(bits) = &value {
+            if bits.len() != obj.size as usize {
+                return Err(RstbError::InvalidValue {
+                    op,
+                    object: obj.name.clone(),
+                    value: bits.clone(),
+                });
+            }
+        }
+        match mode {
+            WriteMode::Force => self.update(hdl, value, true),
+            WriteMode::NoDelay => self.update(hdl, value, false),
+            _ => self.scheduled.push((hdl, value)),
+        }
+        Ok(())
+    }
+    fn update(&mut self, hdl: usize, value: MockValue, force: bool) {
+        let obj = &mut self.objects[hdl - 1];
+        let old = obj.value().clone();
+        if force {
+            obj.forced = Some(value);
+        } else {
+            obj.driven = value;
+        }
+        if *obj.value() != old {
+            self.changes.push_back(hdl);
+        }
+    }
+    fn apply_scheduled(&mut self) {
+        for (hdl, value) in std::mem::take(&mut self.scheduled) {
+            self.update(hdl, value, false);
+        }
+    }
+    fn register(&mut self, cb: MockCallback) -> usize {
+        self.next_cb += 1;
+        self.callbacks.insert(self.next_cb, cb);
+        self.next_cb
+    }
+    fn take_callbacks(&mut self, cb: MockCallback) -> Vec<usize> {
+        let handles: Vec<usize> = self
+            .callbacks
+            .iter()
+            .filter(|(_, c)| **c == cb)
+            .map(|(h, _)| *h)
+            .collect();
+        for h in handles.iter() {
+            self.callbacks.remove(h);
+        }
+        handles
+    }
+    fn next_timer(&self) -> Option<u64> {
+        self.callbacks
+            .values()
+            .filter_map(|c| match c {
+                MockCallback::Time(t) | MockCallback::StartOfTimeStep(t) => Some(*t),
+                _ => None,
+            })
+            .min()
+    }
+}
+
+impl SimIf for Mock {
+    fn set_value_int(&self, obj: usize, value: i32, mode: WriteMode) -> SimResult<()> {
+        let mut state = state();
+        let size = state.object(obj)?.size as usize;
+        let bin = (0..size)
+            .rev()
+            // bits above 32 repeat the sign bit, like simulators extend vpiIntVal
+            .map(|i| match (value >> i.min(31)) & 1 == 1 {
+                true => '1',
+                false => '0',
+            })
+            .collect();
+        state.write(
+            "set_value_int",
+            obj,
+            MockValue::Bits(bin),
+            mode,
+        )
+    }
+    fn get_value_int(&self, obj: usize) -> SimResult<i32> {
+        let state = state();
+        // Like most simulators, X and Z are read as 0.
+        let object = state.object(obj)?;
+        let val = object
+            .bits("get_value_int")?
+            .chars()
+            .fold(0u32, |acc, c| (acc << 1) | (c == '1') as u32);
+        // signed objects are sign extended
+        let shift = 32 - object.size.clamp(1, 32);
+        match object.signed {
+            true => Ok(((val << shift) as i32) >> shift),
+            false => Ok(val as i32),
+        }
+    }
+    fn set_value_bin(&self, obj: usize, value: String, mode: WriteMode) -> SimResult<()> {
+        state().write(
+            "set_value_bin",
+            obj,
+            MockValue::Bits(value.to_ascii_lowercase()),
+            mode,
+        )
+    }
+    fn get_value_bin(&self, obj: usize) -> SimResult<String> {
+        Ok(state().object(obj)?.bits("get_value_bin")?.to_string())
+    }
+    fn set_value_f64(&self, obj: usize, value: f64, mode: WriteMode) -> SimResult<()> {
+        state().write(
+            "set_value_f64",
+            obj,
+            MockValue::Real(value),
+            mode,
+        )
+    }
+    fn get_value_f64(&self, obj: usize) -> SimResult<f64> {
+        let state = state();
+        let object = state.object(obj)?;
+        match object.value() {
+            MockValue::Real(val) => Ok(*val),
+            _ => Err(object.kind_error("get_value_f64")),
+        }
+    }
+    fn set_value_str(&self, obj: usize, value: &str, mode: WriteMode) -> SimResult<()> {
+        state().write(
+            "set_value_str",
+            obj,
+            MockValue::Str(value.to_string()),
+            mode,
+        )
+    }
+    fn get_value_str(&self, obj: usize) -> SimResult<String> {
+        let state = state();
+        let object = state.object(obj)?;
+        match object.value() {
+            MockValue::Str(val) => Ok(val.clone()),
+            _ => Err(object.kind_error("get_value_str")),
+        }
+    }
+    fn release(&self, obj: usize) -> SimResult<()> {
+        let mut state = state();
+        let object = state.object_mut(obj)?;
+        if let Some(forced) = object.forced.take() {
+            if forced != object.driven {
+                state.changes.push_back(obj);
+            }
+        }
+        Ok(())
+    }
+    fn get_handle_by_name(&self, name: &str) -> SimResult<usize> {
+        match state().names.get(name) {
+            Some(hdl) => Ok(*hdl),
+            None => Err(RstbError::NotFound {
+                name: name.to_string(),
+            }),
+        }
+    }
+    fn get_sim_time_steps(&self) -> u64 {
+        state().time
+    }
+    fn log(&self, msg: &str) {
+        println!("{} {}", time_stamp(self.get_sim_time_f64("ns")), msg);
+    }
+    fn get_size(&self, obj: usize) -> i32 {
+        state().object(obj).map(|o| o.size).unwrap_or(0)
+    }
+    fn get_kind(&self, obj: usize) -> ObjectKind {
+        state()
+            .object(obj)
+            .map(|o| o.kind)
+            .unwrap_or(ObjectKind::Other)
+    }
+    fn is_signed(&self, obj: usize) -> bool {
+        state().object(obj).map(|o| o.signed).unwrap_or(false)
+    }
+    fn get_full_name(&self, obj: usize) -> SimResult<String> {
+        Ok(state().object(obj)?.name.clone())
+    }
+    fn get_sim_precision(&self) -> i8 {
+        state().precision
+    }
+    fn get_root_handle(&self) -> SimResult<usize> {
+        match state().root {
+            0 => Err(RstbError::sim(
+                "get_root_handle",
+                "",
+                Some("no top level module".to_string()),
+            )),
+            root => Ok(root),
+        }
+    }
+    fn get_children(&self, obj: usize) -> Vec<usize> {
+        let state = state();
+        let prefix = match state.object(obj) {
+            Ok(o) => format!("{}.", o.name),
+            Err(_) => return Vec::new(),
+        };
+        state
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| {
+                // array elements are not children of the array's scope
+                o.kind != ObjectKind::Param
+                    && o.name
+                        .strip_prefix(&prefix)
+                        .is_some_and(|rest| !rest.contains('.') && !rest.contains('['))
+            })
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+    fn get_ports(&self, obj: usize) -> Vec<(usize, PortDirection)> {
+        let children = self.get_children(obj);
+        let state = state();
+        children
+            .into_iter()
+            .filter_map(|hdl| Some((hdl, state.object(hdl).ok()?.direction?)))
+            .collect()
+    }
+    fn get_params(&self, obj: usize) -> Vec<usize> {
+        let state = state();
+        let prefix = match state.object(obj) {
+            Ok(o) => format!("{}.", o.name),
+            Err(_) => return Vec::new(),
+        };
+        state
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| {
+                o.kind == ObjectKind::Param
+                    && o.name
+                        .strip_prefix(&prefix)
+                        .is_some_and(|rest| !rest.contains('.'))
+            })
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+    fn get_enum_literals(&self, obj: usize) -> Vec<(String, i64)> {
+        match state().object(obj) {
+            Ok(o) => o
+                .literals
+                .iter()
+                .enumerate()
+                .map(|(i, l)| (l.clone(), i as i64))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+    fn get_handle_by_index(&self, obj: usize, index: i32) -> SimResult<usize> {
+        let state = state();
+        let name = format!("{}[{}]", state.object(obj)?.name, index);
+        state
+            .names
+            .get(&name)
+            .copied()
+            .ok_or(RstbError::NotFound { name })
+    }
+    fn get_range(&self, obj: usize) -> SimResult<(i32, i32)> {
+        let state = state();
+        let obj = state.object(obj)?;
+        match obj.kind {
+            ObjectKind::Array => Ok((0, obj.size - 1)),
+            _ => Err(obj.kind_error("get_range")),
+        }
+    }
+    fn register_callback_rw(&self) -> SimResult<usize> {
+        Ok(state().register(MockCallback::ReadWrite))
+    }
+    fn register_callback_ro(&self) -> SimResult<usize> {
+        Ok(state().register(MockCallback::ReadOnly))
+    }
+    fn register_callback_time(&self, t: u64) -> SimResult<usize> {
+        let mut state = state();
+        let abs_time = state.time + t;
+        Ok(state.register(MockCallback::Time(abs_time)))
+    }
+    fn register_callback_next_time_step(&self) -> SimResult<usize> {
+        Ok(state().register(MockCallback::NextTimeStep))
+    }
+    fn register_callback_start_of_time_step(&self, t: u64) -> SimResult<usize> {
+        let mut state = state();
+        let abs_time = state.time + t;
+        Ok(state.register(MockCallback::StartOfTimeStep(abs_time)))
+    }
+    fn register_callback_nba(&self) -> SimResult<usize> {
+        Ok(state().register(MockCallback::Nba))
+    }
+    fn register_callback_edge(&self, sig_hdl: usize) -> SimResult<usize> {
+        let mut state = state();
+        let obj = state.object(sig_hdl)?;
+        // like simulators, value change callbacks can't be put on instances or scopes
+        if matches!(obj.kind, ObjectKind::Module | ObjectKind::Scope) {
+            return Err(RstbError::sim(
+                "register_callback_edge",
+                obj.name.as_str(),
+                Some("object has no value".to_string()),
+            ));
+        }
+        Ok(state.register(MockCallback::Edge(sig_hdl)))
+    }
+    fn cancel_callback(&self, cb_hdl: usize) -> SimResult<()> {
+        match state().callbacks.remove(&cb_hdl) {
+            Some(_) => Ok(()),
+            None => Err(RstbError::sim(
+                "cancel_callback",
+                "",
+                Some(format!("no callback with handle {}", cb_hdl)),
+            )),
+        }
+    }
+    fn control(&self, command: SimControl) -> SimResult<()> {
+        match command {
+            SimControl::Stop | SimControl::Finish => {
+                state().finished = true;
+                Ok(())
+            }
+            // the mock can't restart its processes
+            SimControl::Reset => Err(RstbError::sim(
+                "control",
+                "",
+                Some("reset is not supported".to_string()),
+            )),
+        }
+    }
+    fn args(&self) -> Vec<String> {
+        state().args.clone()
+    }
+    fn product(&self) -> String {
+        "rstb mock".to_string()
+    }
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+}
+
+/// Outcome of `MockSim::run_tests()`
+#[derive(Debug)]
+pub struct MockRun {
+    /// Result of each test, `None` if it was not selected with `+TESTCASE`
+    pub results: Vec<Option<RstbResult>>,
+    /// Names given with `+TESTCASE` that don't match a test
+    pub unknown_tests: Vec<String>,
+    /// Whether the simulation was stopped or finished through `SIM_IF.control()`
+    pub finished: bool,
+}
+
+/// Builder for a mock simulation: declares the DUT hierarchy and its behaviour, then runs a
+/// single test against it.
+pub struct MockSim {
+    root: String,
+    precision: i8,
+    max_time: Option<(u64, String)>,
+    args: Vec<String>,
+    finish_after_tests: bool,
+    objects: Vec<MockDecl>,
+    processes: Vec<(Vec<String>, Process)>,
+}
+
+struct MockDecl {
+    name: String,
+    kind: ObjectKind,
+    size: i32,
+    signed: bool,
+    direction: Option<PortDirection>,
+    // initial value, if it differs from the default for the kind
+    value: Option<MockValue>,
+    literals: Vec<String>,
+}
+
+impl MockSim {
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: root.to_string(),
+            precision: -12,
+            max_time: None,
+            args: Vec::new(),
+            finish_after_tests: false,
+            objects: Vec::new(),
+            processes: Vec::new(),
+        }
+    }
+    /// Time precision as power of 10 in seconds, e.g. -12 for ps (default).
+    pub fn precision(mut self, precision: i8) -> Self {
+        self.precision = precision;
+        self
+    }
+    /// Stops the simulation at the given time, even if there are pending events.
+    pub fn max_time(mut self, time: u64, unit: &str) -> Self {
+        self.max_time = Some((time, unit.to_string()));
+        self
+    }
+    /// Command line arguments returned by `SIM_IF.args()`, e.g. `&["+SEED=5"]`
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args = args.iter().map(|a| a.to_string()).collect();
+        self
+    }
+    /// Calls `finish_after_tests(true)` before the tests start, like a setup function of
+    /// `run_with_vpi!` would.
+    pub fn finish_after_tests(mut self) -> Self {
+        self.finish_after_tests = true;
+        self
+    }
+    /// Adds an unsigned signal. `name` is relative to the root and may contain scopes
+    /// separated by '.', which are created as required.
+    pub fn signal(self, name: &str, size: i32) -> Self {
+        self.add_signal(name, size, false, None)
+    }
+    pub fn signed(self, name: &str, size: i32) -> Self {
+        self.add_signal(name, size, true, None)
+    }
+    /// Adds an unsigned signal which is reported as input port of its parent scope.
+    pub fn input(self, name: &str, size: i32) -> Self {
+        self.add_signal(name, size, false, Some(PortDirection::Input))
+    }
+    /// Adds an unsigned signal which is reported as output port of its parent scope.
+    pub fn output(self, name: &str, size: i32) -> Self {
+        self.add_signal(name, size, false, Some(PortDirection::Output))
+    }
+    /// Adds an unpacked array `name[0:len-1]` of unsigned `width` bit elements.
+    pub fn array(mut self, name: &str, len: i32, width: i32) -> Self {
+        if len < 1 || width < 1 {
+            panic!("Mock array {} must have a positive length and width.", name);
+        }
+        self = self.add_object(name, ObjectKind::Array, len, false, None);
+        for i in 0..len {
+            self = self.add_object(
+                &format!("{}[{}]", name, i),
+                ObjectKind::Bits,
+                width,
+                false,
+                None,
+            );
+        }
+        self
+    }
+    /// Adds a real valued signal, initialized to 0.0.
+    pub fn real(self, name: &str) -> Self {
+        self.add_object(name, ObjectKind::Real, 64, true, None)
+    }
+    /// Adds a string valued signal, initialized to "".
+    pub fn string(self, name: &str) -> Self {
+        self.add_object(name, ObjectKind::String, 0, false, None)
+    }
+    /// Adds an enumeration typed signal, encoded by the literals' indices and initialized to the
+    /// first literal.
+    pub fn enumeration(mut self, name: &str, literals: &[&str]) -> Self {
+        if literals.is_empty() {
+            panic!("Mock enumeration {} must have literals.", name);
+        }
+        let size = (usize::BITS - (literals.len() - 1).leading_zeros()).max(1) as i32;
+        self = self.add_signal(name, size, false, None);
+        let decl = self.objects.last_mut().unwrap();
+        decl.value = Some(MockValue::Bits("0".repeat(size as usize)));
+        decl.literals = literals.iter().map(|l| l.to_string()).collect();
+        self
+    }
+    /// Adds an empty scope (module instance).
+    pub fn scope(self, name: &str) -> Self {
+        self.add_object(name, ObjectKind::Module, 0, false, None)
+    }
+    /// Adds an integer parameter. It is 32 bits wide, like an unsized Verilog parameter, unless
+    /// the value requires 64 bits.
+    pub fn param(self, name: &str, value: i64) -> Self {
+        let size = match i32::try_from(value) {
+            Ok(_) => 32,
+            Err(_) => 64,
+        };
+        let bin = (0..size).rev().map(|i| match (value >> i) & 1 {
+            1 => '1',
+            _ => '0',
+        });
+        self.add_param(name, size, true, MockValue::Bits(bin.collect()))
+    }
+    pub fn param_real(self, name: &str, value: f64) -> Self {
+        self.add_param(name, 64, true, MockValue::Real(value))
+    }
+    pub fn param_str(self, name: &str, value: &str) -> Self {
+        let size = 8 * value.len() as i32;
+        self.add_param(name, size, false, MockValue::Str(value.to_string()))
+    }
+    fn add_param(mut self, name: &str, size: i32, signed: bool, value: MockValue) -> Self {
+        self = self.add_object(name, ObjectKind::Param, size, signed, None);
+        self.objects.last_mut().unwrap().value = Some(value);
+        self
+    }
+    fn add_signal(
+        self,
+        name: &str,
+        size: i32,
+        signed: bool,
+        direction: Option<PortDirection>,
+    ) -> Self {
+        if size < 1 {
+            panic!("Mock signal {} must have a positive size.", name);
+        }
+        self.add_object(name, ObjectKind::Bits, size, signed, direction)
+    }
+    fn add_object(
+        mut self,
+        name: &str,
+        kind: ObjectKind,
+        size: i32,
+        signed: bool,
+        direction: Option<PortDirection>,
+    ) -> Self {
+        self.objects.push(MockDecl {
+            name: name.to_string(),
+            kind,
+            size,
+            signed,
+            direction,
+            value: None,
+            literals: Vec::new(),
+        });
+        self
+    }
+    /// Adds a DUT process, which is executed whenever one of the signals in `sensitivity`
+    /// changes its value. It receives the root object and is executed before any testbench
+    /// callback reacting to the same change.
+    pub fn process(
+        mut self,
+        sensitivity: &[&str],
+        process: impl Fn(SimObject) + Send + Sync + 'static,
+    ) -> Self {
+        let sensitivity = sensitivity.iter().map(|s| s.to_string()).collect();
+        self.processes.push((sensitivity, Arc::new(process)));
+        self
+    }
+
+    /// Runs `generator` as test until it passes or fails, or until there are no more events to
+    /// simulate, and returns the test's result.
+    pub fn run(self, generator: fn(SimObject) -> BoxFuture<'static, RstbResult>) -> RstbResult {
+        let _guard = RUN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.install();
+        systf::register_all();
+
+        let test = RstbObjSafe::new(Test::new("mock".to_string(), generator));
+        let sim_root = SimObject::get_root().unwrap();
+        let test_clone = test.clone();
+        let test_handle = executor::Task::spawn_from_future(async move {
+            match generator(sim_root).await {
+                Ok(val) => crate::pass_test(&format!("{:?}", val)),
+                Err(val) => crate::fail_test(&format!("{:?}", val)),
+            }
+            Ok(Val::None)
+        });
+        let test_task = test_handle.get_task().unwrap();
+        crate::CURRENT_TEST.with_mut(move |mut c| c.replace((test_task, test_clone)));
+        executor::run_once();
+        run_events(
+            self.max_time
+                .map(|(t, unit)| SIM_IF.get_sim_steps(t as f64, &unit)),
+        );
+
+        if test.get().result.is_none() {
+            crate::fail_test("Simulation ended before test completed.");
+        }
+        let result = test.get().result.clone();
+        result.unwrap_or(Err(Val::None))
+    }
+
+    /// Runs `tests` one after another like `run_with_vpi!` does, including the selection with a
+    /// `+TESTCASE` argument and `finish_after_tests()`.
+    pub fn run_tests(self, tests: RstbTests) -> MockRun {
+        let _guard = RUN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.install();
+        systf::register_all();
+
+        // like in a simulation, the tests live until the process ends
+        let tests: &'static RstbTests = Box::leak(Box::new(tests));
+        let unknown_tests = crate::schedule_tests(tests);
+        executor::run_once();
+        run_events(
+            self.max_time
+                .map(|(t, unit)| SIM_IF.get_sim_steps(t as f64, &unit)),
+        );
+
+        let results = tests
+            .iter()
+            .map(|test| {
+                let mut test = test.get();
+                if test.result.is_none() && !test.skipped {
+                    let msg = "Simulation ended before test completed.".to_string();
+                    test.set_result(Err(Val::String(msg)));
+                }
+                test.result.clone()
+            })
+            .collect();
+        MockRun {
+            results,
+            unknown_tests,
+            finished: state().finished,
+        }
+    }
+
+    /// Calls the system task or function `name` like HDL code would, e.g. from a process, and
+    /// returns its result.
+    pub fn call_systf(name: &str, args: &[Val]) -> Val {
+        let id = state().systfs.get(name).copied();
+        match id {
+            Some(id) => systf::call(id, args.to_vec()),
+            None => panic!("Mock has no system task {}.", name),
+        }
+    }
+
+    fn install(&self) {
+        // discard leftovers of previous runs
+        trigger::clear_all_triggers();
+        executor::clear_ready_queue();
+        crate::rstb_obj::clear_objects();
+        crate::signal::clear_signal_cache();
+        crate::CURRENT_TEST.with_mut(|mut c| c.take());
+        crate::finish_after_tests(self.finish_after_tests);
+
+        let mut state = state();
+        *state = MockState {
+            precision: self.precision,
+            args: self.args.clone(),
+            ..Default::default()
+        };
+        state.root = add_object(&mut state, &self.root, ObjectKind::Module, 0, false);
+        for decl in self.objects.iter() {
+            let name = &decl.name;
+            let full_name = format!("{}.{}", self.root, name);
+            // create intermediate scopes
+            let mut scope = self.root.clone();
+            for part in name
+                .split('.')
+                .rev()
+                .skip(1)
+                .collect::<Vec<_>>()
+                .iter()
+                .rev()
+            {
+                scope = format!("{}.{}", scope, part);
+                if !state.names.contains_key(&scope) {
+                    add_object(&mut state, &scope, ObjectKind::Module, 0, false);
+                }
+            }
+            if state.names.contains_key(&full_name) {
+                panic!("Mock object {} defined twice.", full_name);
+            }
+            let hdl = add_object(&mut state, &full_name, decl.kind, decl.size, decl.signed);
+            state.objects[hdl - 1].direction = decl.direction;
+            state.objects[hdl - 1].literals = decl.literals.clone();
+            if let Some(value) = &decl.value {
+                state.objects[hdl - 1].driven = value.clone();
+            }
+        }
+        for (sensitivity, process) in self.processes.iter() {
+            let handles = sensitivity
+                .iter()
+                .map(|s| {
+                    let name = format!("{}.{}", self.root, s);
+                    *state.names.get(&name).unwrap_or_else(|| {
+                        panic!("Mock process sensitive to unknown signal {}.", name)
+                    })
+                })
+                .collect();
+            state.processes.push((handles, process.clone()));
+        }
+    }
+}
+
+// Simulator callbacks which are registered and haven't fired or been cancelled yet
+#[cfg(test)]
+pub(crate) fn pending_callbacks() -> usize {
+    state().callbacks.len()
+}
+
+pub(crate) fn register_systf(name: &str, _kind: SysTfKind, id: usize) -> SimResult<()> {
+    state().systfs.insert(name.to_string(), id);
+    Ok(())
+}
+
+fn add_object(
+    state: &mut MockState,
+    name: &str,
+    kind: ObjectKind,
+    size: i32,
+    signed: bool,
+) -> usize {
+    let driven = match kind {
+        ObjectKind::Real => MockValue::Real(0.0),
+        ObjectKind::String => MockValue::Str(String::new()),
+        ObjectKind::Array => MockValue::Bits(String::new()),
+        _ => MockValue::Bits("x".repeat(size as usize)),
+    };
+    state.objects.push(MockObject {
+        name: name.to_string(),
+        kind,
+        size,
+        signed,
+        direction: None,
+        literals: Vec::new(),
+        driven,
+        forced: None,
+    });
+    let hdl = state.objects.len();
+    state.names.insert(name.to_string(), hdl);
+    hdl
+}
+
+fn run_events(max_time: Option<u64>) {
+    // finishing takes effect before time advances again
+    loop {
+        // delta cycles and ReadWrite synchronization
+        loop {
+            state().apply_scheduled();
+            let change = state().changes.pop_front();
+            if let Some(hdl) = change {
+                react_change(hdl);
+                continue;
+            }
+            // processes have settled, the mock has no separate NBA region
+            let nba = state().take_callbacks(MockCallback::Nba);
+            if !nba.is_empty() {
+                trigger::react_nba();
+                continue;
+            }
+            let rw = state().take_callbacks(MockCallback::ReadWrite);
+            if !rw.is_empty() {
+                trigger::react_rw();
+                continue;
+            }
+            break;
+        }
+        // ReadOnly synchronization, callbacks registered during this phase fire in the next time step
+        let ro = state().take_callbacks(MockCallback::ReadOnly);
+        if !ro.is_empty() {
+            trigger::react_ro();
+            if !state().changes.is_empty() {
+                continue;
+            }
+        }
+        // advance time
+        if state().finished {
+            break;
+        }
+        let next = state().next_timer();
+        match next {
+            Some(t) if max_time.is_none_or(|max| t <= max) => {
+                state().time = t;
+                let next_step = state().take_callbacks(MockCallback::NextTimeStep);
+                if !next_step.is_empty() {
+                    trigger::react_next_time_step();
+                }
+                let start = state().take_callbacks(MockCallback::StartOfTimeStep(t));
+                if !start.is_empty() {
+                    trigger::react_start_of_time_step(t);
+                }
+                // all triggers for this time share one entry in the timer map
+                let timers = state().take_callbacks(MockCallback::Time(t));
+                if !timers.is_empty() {
+                    trigger::react_time(t);
+                }
+            }
+            _ => break,
+        }
+    }
+}
+
+fn react_change(hdl: usize) {
+    let (processes, callbacks, edge) = {
+        let state = state();
+        let processes: Vec<Process> = state
+            .processes
+            .iter()
+            .filter(|(sens, _)| sens.contains(&hdl))
+            .map(|(_, p)| p.clone())
+            .collect();
+        let callbacks: Vec<usize> = state
+            .callbacks
+            .iter()
+            .filter(|(_, c)| **c == MockCallback::Edge(hdl))
+            .map(|(h, _)| *h)
+            .collect();
+        let obj = state.object(hdl).unwrap();
+        let edge = match (obj.size, obj.bits("edge")) {
+            (1, Ok("1")) => EdgeKind::Rising,
+            (1, Ok(_)) => EdgeKind::Falling,
+            _ => EdgeKind::Any,
+        };
+        (processes, callbacks, edge)
+    };
+    if !processes.is_empty() {
+        let root = SimObject::get_root().unwrap();
+        for process in processes {
+            process(root);
+        }
+    }
+    for cb in callbacks {
+        // the callback may have been cancelled by a previous one
+        if state().callbacks.contains_key(&cb) {
+            trigger::react_edge(hdl, edge);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn dff() -> MockSim {
+        MockSim::new("top")
+            .signal("clk", 1)
+            .signal("d", 8)
+            .signal("q", 8)
+            .max_time(10, "us")
+            .process(&["clk"], |dut| {
+                if dut.c("clk").u32() == 1 {
+                    dut.c("q").set(dut.c("d").i32());
+                }
+            })
+    }
+
+    fn now_ns() -> u64 {
+        SIM_IF.get_sim_time("ns")
+    }
+
+    #[test]
+    fn timer_advances_time() {
+        let r = dff().run(|_| {
+            async {
+                Trigger::timer(5, "ns").await;
+                assert_eq!(now_ns(), 5);
+                Trigger::timer_steps(SIM_IF.get_sim_steps(2.0, "ns")).await;
+                assert_eq!(now_ns(), 7);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn read_write_before_read_only() {
+        let r = dff().run(|dut| {
+            async move {
+                let d = dut.c("d");
+                Trigger::timer(1, "ns").await;
+                Trigger::read_write().await;
+                d.set(3);
+                Trigger::read_only().await;
+                assert_eq!(d.u32(), 3);
+                assert_eq!(now_ns(), 1);
+                Trigger::timer_rw(1, "ns").await.unwrap();
+                assert_eq!(now_ns(), 2);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn edges_run_dut_processes() {
+        let r = dff().run(|dut| {
+            async move {
+                let clk = dut.c("clk");
+                Task::spawn(testbench::clock(clk, 10, "ns"));
+                for i in 0..10 {
+                    clk.falling_edge().await;
+                    dut.c("d").set(i);
+                    clk.rising_edge().await;
+                    Trigger::read_only().await;
+                    assert_eq!(dut.c("q").i32(), i);
+                }
+                clk.edge().await;
+                assert_eq!(now_ns(), 100);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn spawn_join_and_cancel() {
+        let r = dff().run(|_| {
+            async {
+                let a = Task::spawn(async {
+                    Trigger::timer(3, "ns").await;
+                    Ok(Val::Int(1))
+                });
+                let b = Task::spawn(async {
+                    Trigger::timer(5, "ns").await;
+                    Ok(Val::Int(2))
+                });
+                let never = Task::spawn(async {
+                    Trigger::timer(1, "ns").await;
+                    panic!("cancelled task executed");
+                });
+                never.cancel();
+                assert_eq!(b.await, Ok(Val::Int(2)));
+                assert_eq!(a.await, Ok(Val::Int(1)));
+                assert_eq!(now_ns(), 5);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn setters_force_and_release() {
+        let sim = MockSim::new("top")
+            .signal("w", 32)
+            .signed("s", 4)
+            .signal("b", 4);
+        let r = sim.run(|dut| {
+            async move {
+                let (w, s, b) = (dut.c("w"), dut.c("s"), dut.c("b"));
+                w.set_u32(0x8000_0001);
+                s.set(-3);
+                b.set_bin("10xz");
+                Trigger::timer(1, "ns").await;
+                assert_eq!(w.u32(), 0x8000_0001);
+                assert_eq!(s.i32(), -3);
+                assert_eq!(b.bin(), "10xz");
+                w.force(7);
+                Trigger::timer(1, "ns").await;
+                w.set(9);
+                Trigger::timer(1, "ns").await;
+                assert_eq!(w.u32(), 7);
+                w.release();
+                w.set(9);
+                Trigger::timer(1, "ns").await;
+                assert_eq!(w.u32(), 9);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn test_results() {
+        let failing = dff().run(|_| async { Err(Val::String("bad".to_string())) }.boxed());
+        assert_eq!(failing, Err(Val::String("String(\"bad\")".to_string())));
+        // nothing drives the clock
+        let hanging = dff().run(|dut| {
+            async move {
+                dut.c("clk").rising_edge().await;
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert_eq!(
+            hanging,
+            Err(Val::String(
+                "Simulation ended before test completed.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn negative_int_on_wide_signal() {
+        let r = MockSim::new("top").signal("u", 64).signed("s", 64).run(|dut| {
+            async move {
+                dut.c("u").set(-5);
+                dut.c("s").set(-5);
+                Trigger::read_only().await;
+                assert_eq!(dut.c("u").u64(), (-5i64) as u64);
+                assert_eq!(dut.c("s").i64(), -5);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn control_finishes_simulation() {
+        let r = dff().run(|_| {
+            async {
+                assert!(SIM_IF.control(SimControl::Reset).is_err());
+                Trigger::timer(1, "ns").await;
+                SIM_IF.control(SimControl::Finish).unwrap();
+                Trigger::timer(1, "ns").await;
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        let msg = "Simulation ended before test completed.";
+        assert_eq!(r, Err(Val::String(msg.to_string())));
+    }
+
+    #[test]
+    fn plusarg_takes_first_value() {
+        let sim = dff().args(&["sim", "+SEED=5", "+SEED=7", "+VERBOSE"]);
+        let r = sim.run(|_| {
+            async {
+                assert_eq!(SIM_IF.args()[0], "sim");
+                assert_eq!(SIM_IF.plusarg("SEED"), Some("5".to_string()));
+                assert_eq!(SIM_IF.plusarg("VERBOSE"), Some(String::new()));
+                assert_eq!(SIM_IF.plusarg("sim"), None);
+                assert_eq!(SIM_IF.plusargs().len(), 3);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
@@ -4,18 +4,28 @@ pub use crate::assertion::{
 };
 pub use crate::CRATE_NAME;
 pub use crate::executor::{JoinHandle, Task};
+pub use crate::hdl_enum::HdlEnum;
+pub use crate::logic::{Logic, LogicVec, ParseLogicError};
 pub use crate::rstb_obj::{RstbObj, AnyObj, RstbObjSafe};
 pub use crate::signal::SimObject;
-pub use crate::sim_if::SIM_IF;
-pub use crate::trigger::Trigger;
+pub use crate::systf::{register_systf, SysTfKind};
+pub use crate::sim_if::{
+    Interface, ObjectKind, PortDirection, SimControl, Simulator, WriteMode, SIM_IF,
+};
+pub use crate::trigger::{AllOf, FirstOf, Trigger};
 pub use crate::value::Val;
 pub use crate::utils;
+#[cfg(feature = "dpi")]
+pub use crate::dpi::register_dpi;
+#[cfg(feature = "mock")]
+pub use crate::mock::{MockRun, MockSim};
 #[cfg(feature = "vhpi")]
 pub use crate::vhpi_init;
 #[cfg(feature = "vpi")]
 pub use crate::vpi_init;
-pub use crate::{add_assertion, check, combine, fail_test, pass_test};
+pub use crate::{add_assertion, check, combine, fail_test, finish_after_tests, pass_test};
 pub use crate::test::{Test, RstbTests};
-pub use crate::RstbResult;
+pub use crate::{RstbError, RstbResult, SimResult};
 pub use crate::testbench;
+pub use crate::memfile;
 pub use futures::future::FutureExt;
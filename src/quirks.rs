@@ -0,0 +1,102 @@
+// Simulator specific workarounds. Every deviation from the standards that rstb works around is
+// listed here, so it can be fixed for one simulator without affecting the others.
+
+use once_cell::sync::OnceCell;
+
+use crate::sim_if::{Simulator, SIM_IF};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Quirks {
+    // vpiIntVal of signed vectors is returned without sign extension
+    pub int_val_unsigned: bool,
+    // value change callbacks may not carry the new value, it has to be read instead
+    pub edge_value_missing: bool,
+}
+
+// Unknown simulators get all workarounds
+const ALL: Quirks = Quirks {
+    int_val_unsigned: true,
+    edge_value_missing: true,
+};
+
+const QUIRK_TABLE: &[(Simulator, Quirks)] = &[
+    (
+        Simulator::Questa,
+        Quirks {
+            int_val_unsigned: true,
+            edge_value_missing: false,
+        },
+    ),
+    (Simulator::Icarus, Quirks::NONE),
+    (Simulator::Xcelium, Quirks::NONE),
+    (Simulator::Riviera, Quirks::NONE),
+    (Simulator::Vcs, Quirks::NONE),
+    (Simulator::Verilator, Quirks::NONE),
+    (Simulator::Ghdl, Quirks::NONE),
+    (Simulator::Nvc, Quirks::NONE),
+    (Simulator::Mock, Quirks::NONE),
+];
+
+impl Quirks {
+    const NONE: Quirks = Quirks {
+        int_val_unsigned: false,
+        edge_value_missing: false,
+    };
+
+    fn of(simulator: Simulator) -> Quirks {
+        QUIRK_TABLE
+            .iter()
+            .find(|(s, _)| *s == simulator)
+            .map(|(_, q)| *q)
+            .unwrap_or(ALL)
+    }
+}
+
+static QUIRKS: OnceCell<Quirks> = OnceCell::new();
+
+// Workarounds for the simulator that loaded rstb
+pub(crate) fn quirks() -> &'static Quirks {
+    QUIRKS.get_or_init(|| Quirks::of(SIM_IF.simulator()))
+}
+
+pub(crate) fn log_simulator() {
+    SIM_IF.log(&format!(
+        "Simulator: {} {} ({:?})",
+        SIM_IF.product(),
+        SIM_IF.version(),
+        SIM_IF.simulator()
+    ));
+    let quirks = quirks();
+    if *quirks != Quirks::NONE {
+        SIM_IF.log(&format!("Simulator workarounds: {:?}", quirks));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quirks_of_simulators() {
+        let questa = Quirks {
+            int_val_unsigned: true,
+            edge_value_missing: false,
+        };
+        let simulators = [
+            (Simulator::Questa, questa),
+            (Simulator::Icarus, Quirks::NONE),
+            (Simulator::Xcelium, Quirks::NONE),
+            (Simulator::Riviera, Quirks::NONE),
+            (Simulator::Vcs, Quirks::NONE),
+            (Simulator::Verilator, Quirks::NONE),
+            (Simulator::Ghdl, Quirks::NONE),
+            (Simulator::Nvc, Quirks::NONE),
+            (Simulator::Mock, Quirks::NONE),
+            // unknown simulators get every workaround
+            (Simulator::Unknown, ALL),
+        ];
+        for (simulator, quirks) in simulators {
+            assert_eq!(Quirks::of(simulator), quirks, "{:?}", simulator);
+        }
+    }
+}
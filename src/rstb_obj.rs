@@ -1,3 +1,5 @@
+#![allow(static_mut_refs)]
+
 
 use std::{
     cell::{Ref, RefCell, RefMut},
@@ -20,6 +22,7 @@ static mut OBJ_CNT: u64 = 0;
 #[derive(Default)]
 pub struct AnyObj<T>(u64, PhantomData<T>);
 
+#[allow(clippy::non_canonical_clone_impl)]
 impl<T> Clone for AnyObj<T> {
     fn clone(&self) -> Self {
         Self(self.0, PhantomData)
@@ -58,14 +61,14 @@ impl<T: 'static> AnyObj<T> {
         f(b);
     }
 
-    pub fn get(&self) -> Ref<T> {
+    pub fn get(&self) -> Ref<'_, T> {
         let a = unsafe { OBJ_MAP.get(self.0).unwrap()};
         let b = a.borrow();
         Ref::map(b, |a| {
             a.downcast_ref::<T>().unwrap()
         })
     }
-    pub fn get_mut(&self) -> RefMut<T> {
+    pub fn get_mut(&self) -> RefMut<'_, T> {
         let a = unsafe { OBJ_MAP.get(self.0).unwrap()};
         let b = a.borrow_mut();
         RefMut::map(b, |a| {
@@ -94,10 +97,10 @@ impl<T> RstbObj<T> {
     pub fn new(val: T) -> RstbObj<T> {
         RstbObj(Rc::new(RefCell::new(val)))
     }
-    pub fn get(&self) -> Ref<T> {
+    pub fn get(&self) -> Ref<'_, T> {
         (*self.0).borrow()
     }
-    pub fn get_mut(&self) -> RefMut<T> {
+    pub fn get_mut(&self) -> RefMut<'_, T> {
         (*self.0).borrow_mut()
     }
     pub fn replace(&self, val: T) {
@@ -124,10 +127,10 @@ impl<T> RstbObjSafe<T> {
     pub fn new(data: T) -> RstbObjSafe<T> {
         RstbObjSafe(Arc::new(Mutex::new(data)))
     }
-    pub fn get(&self) -> MutexGuard<T> {
+    pub fn get(&self) -> MutexGuard<'_, T> {
         (*self.0).try_lock().unwrap()
     }
-    pub fn get_mut(&self) -> MutexGuard<T> {
+    pub fn get_mut(&self) -> MutexGuard<'_, T> {
         (*self.0).try_lock().unwrap()
     }
     pub fn with_mut<F: FnOnce(MutexGuard<T>) -> R, R>(&self, f: F) -> R {
@@ -1,11 +1,18 @@
-#![allow(clippy::result_unit_err)]
+#![allow(static_mut_refs)]
 
 use lazy_mut::lazy_mut;
 use intmap::IntMap;
 use crate::{RstbResult, seamap::SeaMap};
-use crate::sim_if::{ObjectKind, SIM_IF};
-use crate::SimpleResult;
-use crate::trigger::Trigger;
+use crate::sim_if::{
+    bin_to_vec, get_interface, interfaces, vec_to_bin, Interface, ObjectKind, PortDirection,
+    SimIf, VecWord, WriteMode, SIM_IF,
+};
+use crate::{RstbError, SimResult};
+use crate::trigger::{self, SyncRegion, Trigger};
+use crate::executor::{JoinHandle, Task};
+use crate::logic::{LogicVec, ParseLogicError};
+use crate::hdl_enum::{same_literal, HdlEnum};
+use crate::quirks::quirks;
 use crate::value::Val;
 
 lazy_mut! {
@@ -14,13 +21,82 @@ lazy_mut! {
 lazy_mut! {
     static mut SIG_MAP: IntMap<SimObject> = IntMap::new();
 }
+lazy_mut! {
+    // delayed writes which a later inertial write cancels, key is the signal handle
+    static mut PENDING_WRITES: IntMap<Vec<PendingWrite>> = IntMap::new();
+}
+static mut NEXT_WRITE_ID: u64 = 0;
+lazy_mut! {
+    // writes made outside of the ReadWrite synchronization region, applied together in the next one
+    static mut WRITE_QUEUE: Vec<(SimObject, PutValue, WriteMode)> = Vec::new();
+}
+
+lazy_mut! {
+    // Vector values written in the current ReadWrite synchronization region. The simulator shows
+    // them only once the region's callback returns, so emulated selects resolve the value of their
+    // parent against them instead of reading it.
+    static mut RW_WRITES: IntMap<PutValue> = IntMap::new();
+}
+
+pub(crate) fn has_queued_writes() -> bool {
+    unsafe { !WRITE_QUEUE.is_empty() }
+}
+
+pub(crate) fn apply_queued_writes() {
+    // called when the ReadWrite region is entered, the writes of the previous one are visible now
+    unsafe { RW_WRITES.clear() };
+    let writes = unsafe { std::mem::take(&mut *WRITE_QUEUE) };
+    for (obj, val, mode) in writes {
+        if let Err(e) = obj.apply(val, mode) {
+            fail_write(e);
+        }
+    }
+}
+
+struct PendingWrite {
+    id: u64,
+    select: Option<Select>,
+    task: JoinHandle,
+}
+
+// The tasks of delayed writes end with the test, since their timers are cancelled
+pub(crate) fn clear_pending_writes() {
+    unsafe { PENDING_WRITES.clear() };
+}
+
+#[cfg(feature = "mock")]
+pub(crate) fn clear_signal_cache() {
+    clear_pending_writes();
+    unsafe {
+        SIG_MAP.clear();
+        *SIG_MAP_NAME = SeaMap::new();
+        WRITE_QUEUE.clear();
+        RW_WRITES.clear();
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 pub struct SimObject {
     handle: usize,
+    interface: Interface,
     kind: ObjectKind,
     size: i32,
     _signed: bool,
+    // Set for bit and part selects which the simulator has no handle for. They are emulated on
+    // the value of the parent vector, which `handle` refers to.
+    select: Option<Select>,
+    mode: WriteMode,
+    // delay of writes in sim steps
+    delay: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Select {
+    // bit offset of the LSB within the parent vector
+    offset: i32,
+    // HDL indices, for naming only
+    msb: i32,
+    lsb: i32,
 }
 
 impl SimObject {
@@ -28,34 +104,100 @@ impl SimObject {
         self.handle
     }
 
+    /// Simulator interface which owns this object's handle
+    pub fn interface(&self) -> Interface {
+        self.interface
+    }
+
+    #[inline]
+    pub(crate) fn sim_if(&self) -> &'static (dyn SimIf + Sync) {
+        get_interface(self.interface)
+    }
+
     pub fn kind(&self) -> ObjectKind {
         self.kind
     }
 
     pub fn name(&self) -> String {
-        SIM_IF
+        let name = self
+            .sim_if()
             .get_full_name(self.handle)
-            .expect("Couldn't get name of ObjectInner")
+            .expect("Couldn't get name of ObjectInner");
+        match self.select {
+            Some(s) if s.msb == s.lsb => format!("{}[{}]", name, s.msb),
+            Some(s) => format!("{}[{}:{}]", name, s.msb, s.lsb),
+            None => name,
+        }
     }
 
     pub fn size(&self) -> i32 {
         self.size
     }
 
+    pub fn write_mode(&self) -> WriteMode {
+        self.mode
+    }
+
+    /// Copy of this object which writes values with `mode`, e.g.
+    /// `sig.with_mode(WriteMode::NoDelay).set(1)`
+    pub fn with_mode(&self, mode: WriteMode) -> SimObject {
+        SimObject { mode, ..*self }
+    }
+
+    /// Copy of this object whose writes are applied after a delay, e.g. `sig.after(5, "ns").set(1)`.
+    /// With `WriteMode::Inertial`, a write discards the object's pending delayed writes.
+    pub fn after(&self, time: u64, unit: &str) -> SimObject {
+        SimObject {
+            delay: SIM_IF.get_sim_steps(time as f64, unit),
+            ..*self
+        }
+    }
+
+    /// Shorthands for `after(time, unit).set*(val)`
+    pub fn set_after(&self, val: i32, time: u64, unit: &str) {
+        self.after(time, unit).set(val)
+    }
+
+    pub fn set_u64_after(&self, val: u64, time: u64, unit: &str) {
+        self.after(time, unit).set_u64(val)
+    }
+
+    pub fn set_f64_after(&self, val: f64, time: u64, unit: &str) {
+        self.after(time, unit).set_f64(val)
+    }
+
+    pub fn set_bin_after(&self, val: &str, time: u64, unit: &str) {
+        self.after(time, unit).set_bin(val)
+    }
+
+    pub fn set_logic_after(&self, val: &LogicVec, time: u64, unit: &str) {
+        self.after(time, unit).set_logic(val)
+    }
+
     pub fn is_signed(&self) -> bool {
-        SIM_IF.is_signed(self.handle)
+        self.select.is_none() && self.sim_if().is_signed(self.handle)
     }
 
     pub fn is_modifiable(&self) -> bool {
-        !matches!(self.kind, ObjectKind::Other)
+        matches!(
+            self.kind,
+            ObjectKind::Bits | ObjectKind::Real | ObjectKind::String | ObjectKind::Array
+        )
     }
 
     pub fn has_value(&self) -> bool {
-        !matches!(self.kind, ObjectKind::Other)
+        matches!(
+            self.kind,
+            ObjectKind::Bits
+                | ObjectKind::Real
+                | ObjectKind::String
+                | ObjectKind::Array
+                | ObjectKind::Param
+        )
     }
 
     #[allow(clippy::needless_question_mark)] // it actueally is necessary
-    pub fn get_child(&self, name: &str) -> SimpleResult<Self> {
+    pub fn get_child(&self, name: &str) -> SimResult<Self> {
         let mut child_name = self.name();
         child_name.push('.');
         child_name.push_str(name);
@@ -63,15 +205,17 @@ impl SimObject {
     }
 
     #[allow(clippy::clone_on_copy)]
-    pub fn from_handle(handle: usize) -> SimpleResult<Self> {
+    pub fn from_handle(handle: usize) -> SimResult<Self> {
         if let Some(signal) = unsafe { SIG_MAP.get(handle as u64) } {
             Ok(signal.clone())
         } else {
-            Err(())
+            Err(RstbError::NotFound {
+                name: format!("handle {:#x}", handle),
+            })
         }
     }
 
-    pub fn from_name(full_name: &str) -> SimpleResult<Self> {
+    pub fn from_name(full_name: &str) -> SimResult<Self> {
         let handle = unsafe { SIG_MAP_NAME.get(full_name) }
             .map(|h| h.to_owned());
         match handle {
@@ -80,17 +224,29 @@ impl SimObject {
         }
     }
 
-    fn new_from_name(full_name: &str) -> SimpleResult<Self> {
-        let handle = SIM_IF.get_handle_by_name(full_name)?;
-        Ok(SimObject::new_from_handle(handle))
+    fn new_from_name(full_name: &str) -> SimResult<Self> {
+        // In mixed-language simulations, objects of either language may be anywhere in the hierarchy.
+        for interface in interfaces() {
+            if let Ok(handle) = get_interface(interface).get_handle_by_name(full_name) {
+                return Ok(SimObject::new_from_handle(handle, interface));
+            }
+        }
+        Err(RstbError::NotFound {
+            name: full_name.to_string(),
+        })
     }
 
-    fn new_from_handle(handle: usize) -> Self {
+    fn new_from_handle(handle: usize, interface: Interface) -> Self {
+        let sim_if = get_interface(interface);
         let signal = SimObject {
             handle,
-            kind: SIM_IF.get_kind(handle),
-            size: SIM_IF.get_size(handle),
-            _signed: SIM_IF.is_signed(handle),
+            interface,
+            kind: sim_if.get_kind(handle),
+            size: sim_if.get_size(handle),
+            _signed: sim_if.is_signed(handle),
+            select: None,
+            mode: WriteMode::default(),
+            delay: 0,
         };
         unsafe {
             SIG_MAP.insert(handle as u64, signal);
@@ -99,122 +255,1021 @@ impl SimObject {
         signal
     }
 
-    pub fn get_root() -> SimpleResult<Self> {
-        Ok(SimObject::new_from_handle(SIM_IF.get_root_handle()?))
+    pub fn get_root() -> SimResult<Self> {
+        let mut error = None;
+        for interface in interfaces() {
+            match get_interface(interface).get_root_handle() {
+                Ok(handle) => return Ok(SimObject::new_from_handle(handle, interface)),
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap())
     }
 
-    // pub fn discover_nets(&self) -> Vec<SimObject> {
-    //     let obj_list = vpi::discover_nets(self.handle);
-    //     let mut signal_list = Vec::with_capacity(obj_list.len());
-    //     for obj in obj_list {
-    //         signal_list.push(SimObject::new_from_handle(obj));
-    //     }
-    //     signal_list
-    // }
+    // Handles returned by iteration or indexing may differ from the ones already known for the
+    // same object.
+    fn from_iterated(handle: usize, interface: Interface) -> SimResult<Self> {
+        let name = get_interface(interface).get_full_name(handle)?;
+        let known = unsafe { SIG_MAP_NAME.get(&name) }.map(|h| h.to_owned());
+        match known {
+            Some(h) => SimObject::from_handle(h),
+            None => Ok(SimObject::new_from_handle(handle, interface)),
+        }
+    }
 
-    pub fn i32(&self) -> i32 {
-        if matches!(self.kind, ObjectKind::Bits) && self.size <= 32 {
-            let val = SIM_IF.get_value_int(self.handle()).unwrap() as i64;
-            // Some simulators don't return negative value for any vector size (Questa)
-            let ceil = 1i64 << (self.size - 1);
-            if val >= ceil {
-                (-2 * ceil + val) as i32
-            } else {
-                val as i32
+    /// All objects directly below this one: signals, arrays, instances and generate scopes.
+    pub fn children(&self) -> Vec<SimObject> {
+        let mut children: Vec<SimObject> = Vec::new();
+        for hdl in self.sim_if().get_children(self.handle) {
+            if let Ok(child) = SimObject::from_iterated(hdl, self.interface) {
+                if !children.iter().any(|c| c.handle == child.handle) {
+                    children.push(child);
+                }
+            }
+        }
+        children
+    }
+
+    /// Nets, regs and variables directly below this one
+    pub fn signals(&self) -> Vec<SimObject> {
+        self.children_of_kind(&[ObjectKind::Bits, ObjectKind::Real])
+    }
+
+    /// Module / entity instances directly below this one
+    pub fn instances(&self) -> Vec<SimObject> {
+        self.children_of_kind(&[ObjectKind::Module])
+    }
+
+    /// Generate scopes and named blocks directly below this one
+    pub fn scopes(&self) -> Vec<SimObject> {
+        self.children_of_kind(&[ObjectKind::Scope])
+    }
+
+    pub fn arrays(&self) -> Vec<SimObject> {
+        self.children_of_kind(&[ObjectKind::Array])
+    }
+
+    fn children_of_kind(&self, kinds: &[ObjectKind]) -> Vec<SimObject> {
+        self.children()
+            .into_iter()
+            .filter(|c| kinds.contains(&c.kind))
+            .collect()
+    }
+
+    /// Left and right bound of an array, e.g. (0, 255) for `reg [7:0] mem [0:255]`
+    pub fn range(&self) -> (i32, i32) {
+        self.check_array("range");
+        self.sim_if()
+            .get_range(self.handle)
+            .unwrap_or((0, self.size - 1))
+    }
+
+    /// Number of elements of an array. HDL arrays always have at least one element.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        let (left, right) = self.range();
+        (left - right).unsigned_abs() as usize + 1
+    }
+
+    /// Array element at HDL index `i`
+    pub fn index(&self, i: i32) -> SimObject {
+        or_panic(self.try_index(i))
+    }
+
+    pub fn try_index(&self, i: i32) -> SimResult<SimObject> {
+        if !matches!(self.kind, ObjectKind::Array) {
+            return Err(self.kind_error("index"));
+        }
+        self.sim_if()
+            .get_handle_by_index(self.handle, i)
+            .and_then(|hdl| SimObject::from_iterated(hdl, self.interface))
+    }
+
+    /// Array elements in order of ascending index
+    pub fn elements(&self) -> Vec<SimObject> {
+        let (left, right) = self.range();
+        (left.min(right)..=left.max(right))
+            .map(|i| self.index(i))
+            .collect()
+    }
+
+    /// Values of all array elements in order of ascending index. Every element is read with
+    /// its own simulator call, so reading large memories through a backdoor takes a while.
+    pub fn read_all(&self) -> Vec<LogicVec> {
+        self.elements().iter().map(|e| e.logic()).collect()
+    }
+
+    /// Writes `vals` to the array elements, starting at the lowest index. Like `read_all()`,
+    /// it accesses one element per simulator call.
+    pub fn write_all(&self, vals: &[LogicVec]) {
+        let elements = self.elements();
+        if vals.len() > elements.len() {
+            panic!(
+                "Can't write {} values to array {} with {} elements.",
+                vals.len(),
+                self.name(),
+                elements.len()
+            );
+        }
+        for (element, val) in elements.iter().zip(vals) {
+            element.set_logic(val);
+        }
+    }
+
+    #[inline]
+    fn check_array(&self, op: &str) {
+        if !matches!(self.kind, ObjectKind::Array) {
+            panic!("Can't use {}() on {} of kind {:?}", op, self.name(), self.kind);
+        }
+    }
+
+    /// Bit `i` of a vector, `i` being the HDL index
+    pub fn bit(&self, i: i32) -> SimObject {
+        or_panic(self.try_bit(i))
+    }
+
+    pub fn try_bit(&self, i: i32) -> SimResult<SimObject> {
+        if !matches!(self.kind, ObjectKind::Bits) {
+            return Err(self.kind_error("bit"));
+        }
+        // prefer a native handle, if the simulator provides one
+        if self.select.is_none() {
+            let native = self
+                .sim_if()
+                .get_handle_by_index(self.handle, i)
+                .and_then(|hdl| SimObject::from_iterated(hdl, self.interface));
+            if let Ok(bit) = native {
+                if bit.kind == ObjectKind::Bits && bit.size == 1 {
+                    return Ok(bit);
+                }
+            }
+        }
+        self.new_select(i, i)
+    }
+
+    /// Part select `[msb:lsb]` of a vector, in terms of the vector's HDL indices. Always emulated
+    /// on the parent vector: writes are read-modify-write of the whole vector, and
+    /// forcing or releasing a slice forces or releases the whole vector.
+    pub fn slice(&self, msb: i32, lsb: i32) -> SimObject {
+        or_panic(self.try_slice(msb, lsb))
+    }
+
+    pub fn try_slice(&self, msb: i32, lsb: i32) -> SimResult<SimObject> {
+        if !matches!(self.kind, ObjectKind::Bits) {
+            return Err(self.kind_error("slice"));
+        }
+        self.new_select(msb, lsb)
+    }
+
+    fn new_select(&self, msb: i32, lsb: i32) -> SimResult<SimObject> {
+        // select of a select refers to the indices of the outer select, which start at 0
+        let (base, left, right) = match self.select {
+            Some(s) => (s.offset, self.size - 1, 0),
+            None => {
+                let range = self.sim_if().get_range(self.handle);
+                let (left, right) = range.unwrap_or((self.size - 1, 0));
+                (0, left, right)
+            }
+        };
+        // offset from the LSB, which is the right bound
+        let offset = |i: i32| match left >= right {
+            true => i - right,
+            false => right - i,
+        };
+        let (hi, lo) = (offset(msb), offset(lsb));
+        if lo < 0 || hi < lo || hi >= self.size {
+            let name = match msb == lsb {
+                true => format!("{}[{}]", self.name(), msb),
+                false => format!("{}[{}:{}]", self.name(), msb, lsb),
+            };
+            return Err(RstbError::NotFound { name });
+        }
+        let (msb, lsb) = match self.select {
+            Some(s) if s.msb >= s.lsb => (s.lsb + hi, s.lsb + lo),
+            Some(s) => (s.lsb - hi, s.lsb - lo),
+            None => (msb, lsb),
+        };
+        Ok(SimObject {
+            size: hi - lo + 1,
+            _signed: false,
+            select: Some(Select {
+                offset: base + lo,
+                msb,
+                lsb,
+            }),
+            ..*self
+        })
+    }
+
+    pub(crate) fn is_emulated_select(&self) -> bool {
+        self.select.is_some()
+    }
+
+    // Value access. Emulated selects read and write the parent vector's binary value.
+    fn read_int(&self) -> SimResult<i32> {
+        match self.select {
+            None => self.sim_if().get_value_int(self.handle),
+            // X and Z are read as 0, like simulators do
+            Some(_) => Ok(self
+                .read_bin()?
+                .chars()
+                .fold(0u32, |acc, c| (acc << 1) | matches!(c, '1' | 'H' | 'h') as u32)
+                as i32),
+        }
+    }
+
+    fn read_vec(&self) -> SimResult<Vec<VecWord>> {
+        match self.select {
+            None => self.sim_if().get_value_vec(self.handle),
+            Some(_) => Ok(bin_to_vec(&self.read_bin()?)),
+        }
+    }
+
+    // Value of the parent vector of an emulated select, including writes of the current ReadWrite
+    // region which the simulator doesn't show yet
+    fn read_parent_bin(&self) -> SimResult<String> {
+        let written = match trigger::sync_region() {
+            SyncRegion::ReadWrite => unsafe { RW_WRITES.get(self.handle as u64) },
+            _ => None,
+        };
+        let size = || self.sim_if().get_size(self.handle) as usize;
+        match written {
+            // simulators sign extend integers written to wider vectors
+            Some(PutValue::Int(v)) => Ok((0..size())
+                .rev()
+                .map(|i| match (v >> i.min(31)) & 1 {
+                    1 => '1',
+                    _ => '0',
+                })
+                .collect()),
+            Some(PutValue::Vec(words)) => Ok(vec_to_bin(words, size())),
+            Some(PutValue::Bin(bin)) if bin.len() == size() => Ok(bin.clone()),
+            _ => self.sim_if().get_value_bin(self.handle),
+        }
+    }
+
+    // Remembers a write in the ReadWrite region until the simulator shows it, see `RW_WRITES`
+    fn remember_write(&self, mode: WriteMode, val: impl FnOnce() -> PutValue) {
+        if trigger::sync_region() != SyncRegion::ReadWrite {
+            return;
+        }
+        unsafe {
+            match mode {
+                // applied right away
+                WriteMode::NoDelay | WriteMode::Force => {
+                    RW_WRITES.remove(self.handle as u64);
+                }
+                _ => {
+                    RW_WRITES.insert(self.handle as u64, val());
+                }
+            }
+        }
+    }
+
+    fn read_bin(&self) -> SimResult<String> {
+        let bin = self.sim_if().get_value_bin(self.handle)?;
+        match self.select {
+            None => Ok(bin),
+            Some(s) => {
+                let end = bin.len() - s.offset as usize;
+                Ok(bin[end - self.size as usize..end].to_string())
+            }
+        }
+    }
+
+    #[inline]
+    fn kind_error(&self, op: &'static str) -> RstbError {
+        RstbError::Kind {
+            op,
+            object: self.name(),
+            kind: self.kind,
+        }
+    }
+
+    // Writes `val` with this object's write mode and delay, or forced. Delayed writes are
+    // scheduled on a timer, since not every interface supports them. A transport delayed write
+    // is always applied, other delayed writes are cancelled by a later inertial write.
+    fn put(&self, val: PutValue, force: bool) -> SimResult<()> {
+        let mode = match force {
+            true => WriteMode::Force,
+            false => self.mode,
+        };
+        if mode == WriteMode::Inertial {
+            self.cancel_pending_writes();
+        }
+        if self.delay == 0 {
+            return self.put_now(val, mode);
+        }
+        let obj = SimObject { delay: 0, ..*self };
+        let delay = self.delay;
+        let id = unsafe {
+            NEXT_WRITE_ID += 1;
+            NEXT_WRITE_ID
+        };
+        let task = Task::spawn(async move {
+            Trigger::timer_steps(delay).await;
+            if mode != WriteMode::Transport {
+                obj.remove_pending_writes(|w| w.id == id);
+            }
+            if let Err(e) = obj.put_now(val, mode) {
+                fail_write(e);
+            }
+            Ok(Val::None)
+        });
+        if mode == WriteMode::Transport {
+            return Ok(());
+        }
+        let pending = PendingWrite {
+            id,
+            select: self.select,
+            task,
+        };
+        unsafe {
+            match PENDING_WRITES.get_mut(self.handle as u64) {
+                Some(writes) => writes.push(pending),
+                None => {
+                    PENDING_WRITES.insert(self.handle as u64, vec![pending]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Like cocotb, writes are collected and applied together in the ReadWrite synchronization
+    // region, so they don't race with HDL processes. NoDelay writes are applied right away.
+    fn put_now(&self, val: PutValue, mode: WriteMode) -> SimResult<()> {
+        match trigger::sync_region() {
+            SyncRegion::ReadOnly => Err(RstbError::ReadOnly {
+                object: self.name(),
+            }),
+            SyncRegion::ReadWrite => self.apply(val, mode),
+            _ if mode == WriteMode::NoDelay => self.apply(val, mode),
+            _ => {
+                unsafe { WRITE_QUEUE.push((*self, val, mode)) };
+                trigger::request_read_write();
+                Ok(())
+            }
+        }
+    }
+
+    fn apply(&self, val: PutValue, mode: WriteMode) -> SimResult<()> {
+        match val {
+            PutValue::Int(v) => self.write_int(v, mode),
+            PutValue::Vec(words) => self.write_vec(&words, mode),
+            PutValue::Bin(bin) => self.write_bin(bin, mode),
+            PutValue::Real(v) => self.sim_if().set_value_f64(self.handle, v, mode),
+            PutValue::Str(v) => self.sim_if().set_value_str(self.handle, &v, mode),
+            PutValue::Release => {
+                self.remember_write(WriteMode::NoDelay, || PutValue::Release);
+                self.sim_if().release(self.handle)
+            }
+        }
+    }
+
+    fn cancel_pending_writes(&self) {
+        for w in self.remove_pending_writes(|w| w.select == self.select) {
+            w.task.cancel();
+        }
+    }
+
+    fn remove_pending_writes(&self, f: impl Fn(&PendingWrite) -> bool) -> Vec<PendingWrite> {
+        let key = self.handle as u64;
+        let mut removed = Vec::new();
+        if let Some(pending) = unsafe { PENDING_WRITES.get_mut(key) } {
+            let kept;
+            (removed, kept) = pending.drain(..).partition(f);
+            *pending = kept;
+            if pending.is_empty() {
+                unsafe { PENDING_WRITES.remove(key) };
+            }
+        }
+        removed
+    }
+
+    fn write_int(&self, val: i32, mode: WriteMode) -> SimResult<()> {
+        match self.select {
+            None => {
+                self.remember_write(mode, || PutValue::Int(val));
+                self.sim_if().set_value_int(self.handle, val, mode)
+            }
+            Some(_) => {
+                let bin = (0..self.size)
+                    .rev()
+                    .map(|i| match i < 32 && (val >> i) & 1 == 1 {
+                        true => '1',
+                        false => '0',
+                    })
+                    .collect();
+                self.write_bin(bin, mode)
+            }
+        }
+    }
+
+    fn write_vec(&self, words: &[VecWord], mode: WriteMode) -> SimResult<()> {
+        match self.select {
+            None => {
+                self.remember_write(mode, || PutValue::Vec(words.to_vec()));
+                self.sim_if().set_value_vec(self.handle, words, mode)
+            }
+            Some(_) => self.write_bin(vec_to_bin(words, self.size as usize), mode),
+        }
+    }
+
+    fn write_bin(&self, bin: String, mode: WriteMode) -> SimResult<()> {
+        match self.select {
+            None => {
+                self.remember_write(mode, || PutValue::Bin(bin.clone()));
+                self.sim_if().set_value_bin(self.handle, bin, mode)
             }
+            Some(s) => {
+                let mut parent = self.read_parent_bin()?;
+                let end = parent.len() - s.offset as usize;
+                parent.replace_range(end - self.size as usize..end, &bin);
+                self.remember_write(mode, || PutValue::Bin(parent.clone()));
+                self.sim_if().set_value_bin(self.handle, parent, mode)
+            }
+        }
+    }
+
+    /// Ports of this instance with their direction
+    pub fn ports(&self) -> Vec<(SimObject, PortDirection)> {
+        self.sim_if()
+            .get_ports(self.handle)
+            .into_iter()
+            .filter_map(|(hdl, dir)| {
+                SimObject::from_iterated(hdl, self.interface)
+                    .ok()
+                    .map(|obj| (obj, dir))
+            })
+            .collect()
+    }
+
+    /// Parameters, generics and constants of this instance
+    pub fn params(&self) -> Vec<SimObject> {
+        self.sim_if()
+            .get_params(self.handle)
+            .into_iter()
+            .filter_map(|hdl| SimObject::from_iterated(hdl, self.interface).ok())
+            .collect()
+    }
+
+    /// Value of an integer, enumeration or vector parameter. Signed parameters are sign extended.
+    pub fn param_i64(&self) -> i64 {
+        or_panic(self.try_param_i64())
+    }
+
+    pub fn try_param_i64(&self) -> SimResult<i64> {
+        if !matches!(self.kind, ObjectKind::Param) || self.size > 64 {
+            return Err(self.kind_error("param_i64"));
+        }
+        let size = self.size.max(1) as u32;
+        // integer generics have no vector representation in VHPI
+        let val = match size {
+            0..=32 => self
+                .sim_if()
+                .get_value_int(self.handle)
+                .map(|v| v as u32 as u64),
+            _ => self.sim_if().get_value_vec(self.handle).map(|words| {
+                words
+                    .iter()
+                    .take(2)
+                    .enumerate()
+                    .fold(0, |acc, (i, w)| acc | ((w.aval & !w.bval) as u64) << (32 * i))
+            }),
+        }?;
+        let shift = 64 - size;
+        match self.is_signed() {
+            true => Ok(((val << shift) as i64) >> shift),
+            false => Ok(((val << shift) >> shift) as i64),
+        }
+    }
+
+    pub fn param_real(&self) -> f64 {
+        or_panic(self.try_param_real())
+    }
+
+    pub fn try_param_real(&self) -> SimResult<f64> {
+        if !matches!(self.kind, ObjectKind::Param) {
+            return Err(self.kind_error("param_real"));
+        }
+        self.sim_if().get_value_f64(self.handle)
+    }
+
+    pub fn param_str(&self) -> String {
+        or_panic(self.try_param_str())
+    }
+
+    pub fn try_param_str(&self) -> SimResult<String> {
+        if !matches!(self.kind, ObjectKind::Param) {
+            return Err(self.kind_error("param_str"));
+        }
+        self.sim_if().get_value_str(self.handle)
+    }
+
+    pub fn i32(&self) -> i32 {
+        or_panic(self.try_i32())
+    }
+
+    pub fn try_i32(&self) -> SimResult<i32> {
+        if !matches!(self.kind, ObjectKind::Bits) || self.size > 32 {
+            return Err(self.kind_error("i32"));
+        }
+        let val = self.read_int()? as i64;
+        // signed objects are already sign extended, unless the simulator doesn't do it
+        if self.is_signed() && !quirks().int_val_unsigned {
+            return Ok(val as i32);
+        }
+        let ceil = 1i64 << (self.size - 1);
+        if val >= ceil {
+            Ok((-2 * ceil + val) as i32)
         } else {
-            panic!("Couldn't get value of {} as i32 type.", self.name());
+            Ok(val as i32)
         }
     }
 
     pub fn u32(&self) -> u32 {
-        if matches!(self.kind, ObjectKind::Bits) && self.size <= 32 {
-            let val = SIM_IF.get_value_int(self.handle()).unwrap() as i64;
-            let ceil = 1i64 << self.size;
-            if val < 0 {
-                (val + ceil) as u32
-            } else {
-                val as u32
-            }
+        or_panic(self.try_u32())
+    }
+
+    pub fn try_u32(&self) -> SimResult<u32> {
+        if !matches!(self.kind, ObjectKind::Bits) || self.size > 32 {
+            return Err(self.kind_error("u32"));
+        }
+        let val = self.read_int()? as i64;
+        let ceil = 1i64 << self.size;
+        if val < 0 {
+            Ok((val + ceil) as u32)
         } else {
-            panic!("Couldn't get value of {} as u32 type.", self.name());
+            Ok(val as u32)
+        }
+    }
+
+    pub fn u64(&self) -> u64 {
+        or_panic(self.try_u64())
+    }
+
+    pub fn try_u64(&self) -> SimResult<u64> {
+        if self.size > 64 {
+            return Err(self.kind_error("u64"));
+        }
+        Ok(self._get_u128("u64")? as u64)
+    }
+
+    pub fn i64(&self) -> i64 {
+        or_panic(self.try_i64())
+    }
+
+    pub fn try_i64(&self) -> SimResult<i64> {
+        if self.size > 64 {
+            return Err(self.kind_error("i64"));
+        }
+        let val = self._get_u128("i64")? as u64;
+        // sign extend
+        let shift = 64 - self.size.max(1) as u32;
+        Ok(((val << shift) as i64) >> shift)
+    }
+
+    pub fn u128(&self) -> u128 {
+        or_panic(self.try_u128())
+    }
+
+    pub fn try_u128(&self) -> SimResult<u128> {
+        if self.size > 128 {
+            return Err(self.kind_error("u128"));
+        }
+        self._get_u128("u128")
+    }
+
+    /// Value of a vector of any width as little endian bytes. X and Z bits are read as 0.
+    pub fn bytes(&self) -> Vec<u8> {
+        or_panic(self.try_bytes())
+    }
+
+    pub fn try_bytes(&self) -> SimResult<Vec<u8>> {
+        let mut bytes: Vec<u8> = self
+            ._get_words("bytes")?
+            .iter()
+            .flat_map(|w| (w.aval & !w.bval).to_le_bytes())
+            .collect();
+        bytes.truncate((self.size.max(1) as usize).div_ceil(8));
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn _get_u128(&self, op: &'static str) -> SimResult<u128> {
+        Ok(self
+            ._get_words(op)?
+            .iter()
+            .take(4)
+            .enumerate()
+            .fold(0, |acc, (i, w)| acc | ((w.aval & !w.bval) as u128) << (32 * i)))
+    }
+
+    #[inline]
+    fn _get_words(&self, op: &'static str) -> SimResult<Vec<VecWord>> {
+        if !matches!(self.kind, ObjectKind::Bits) {
+            return Err(self.kind_error(op));
+        }
+        self.read_vec()
+    }
+
+    pub fn f64(&self) -> f64 {
+        or_panic(self.try_f64())
+    }
+
+    pub fn try_f64(&self) -> SimResult<f64> {
+        if !matches!(self.kind, ObjectKind::Real) {
+            return Err(self.kind_error("f64"));
+        }
+        self.sim_if().get_value_f64(self.handle)
+    }
+
+    pub fn string(&self) -> String {
+        or_panic(self.try_string())
+    }
+
+    pub fn try_string(&self) -> SimResult<String> {
+        if !matches!(self.kind, ObjectKind::String) {
+            return Err(self.kind_error("string"));
+        }
+        self.sim_if().get_value_str(self.handle)
+    }
+
+    /// Current value in the representation that fits the object's kind: `Val::Int` for vectors
+    /// of up to 32 bits, `Val::Logic` for wider ones and for values with X or Z bits,
+    /// `Val::Float`, `Val::String` and `Val::Vec` for arrays.
+    pub fn value(&self) -> Val {
+        match self.kind {
+            ObjectKind::Bits => {
+                let logic = self.logic();
+                match logic.to_u64() {
+                    Some(val) if self.size <= 32 => Val::Int(val as u32),
+                    _ => Val::Logic(logic),
+                }
+            }
+            ObjectKind::Real => Val::Float(self.f64()),
+            ObjectKind::String => Val::String(self.string()),
+            ObjectKind::Array => Val::Vec(self.elements().iter().map(|e| e.value()).collect()),
+            _ => panic!("{} of kind {:?} has no value.", self.name(), self.kind),
+        }
+    }
+
+    /// Literals of an enumeration typed signal with their values (VPI) or positions (VHPI)
+    pub fn enum_literals(&self) -> Vec<(String, i64)> {
+        match self.select {
+            None => self.sim_if().get_enum_literals(self.handle),
+            Some(_) => Vec::new(),
         }
     }
 
+    /// Literal an enumeration typed signal currently holds, e.g. "IDLE". Values without a
+    /// literal, e.g. with X bits, are returned as binary string.
+    pub fn enum_name(&self) -> String {
+        let literals = self.check_enum("enum_name");
+        // unknown bits would be read as 0
+        let bin = self.sim_if().get_value_bin(self.handle).ok();
+        if let Some(bin) = bin.as_ref().filter(|b| b.contains(|c| c != '0' && c != '1')) {
+            return bin.clone();
+        }
+        let val = or_panic(self.read_int()) as i64;
+        literals
+            .into_iter()
+            .find(|(_, v)| *v == val)
+            .map(|(name, _)| name)
+            .unwrap_or_else(|| bin.unwrap_or_else(|| val.to_string()))
+    }
+
+    /// Current value as variant of `T`, see `HdlEnum`.
+    pub fn enum_as<T: HdlEnum>(&self) -> T {
+        let name = self.enum_name();
+        T::from_literal(&name).unwrap_or_else(|| {
+            panic!(
+                "{} holds {}, which is no variant of {}.",
+                self.name(),
+                name,
+                std::any::type_name::<T>()
+            )
+        })
+    }
+
+    #[inline]
+    fn check_enum(&self, op: &str) -> Vec<(String, i64)> {
+        let literals = self.enum_literals();
+        if !matches!(self.kind, ObjectKind::Bits) || literals.is_empty() {
+            panic!("Can't use {}() on {}, which is not enumeration typed.", op, self.name());
+        }
+        literals
+    }
+
     pub fn bin(&self) -> String {
-        SIM_IF.get_value_bin(self.handle).unwrap()
+        or_panic(self.try_bin())
+    }
+
+    pub fn try_bin(&self) -> SimResult<String> {
+        self.read_bin()
+    }
+
+    pub fn logic(&self) -> LogicVec {
+        or_panic(self.try_logic())
+    }
+
+    pub fn try_logic(&self) -> SimResult<LogicVec> {
+        self.try_bin()?
+            .parse()
+            .map_err(|e: ParseLogicError| {
+                RstbError::sim("logic", self.name(), Some(e.to_string()))
+            })
     }
 
     pub fn c(&self, name: &str) -> Self {
+        or_panic(self.try_c(name))
+    }
+
+    /// Like `c()`, but returns `RstbError::NotFound` instead of panicking if there is no child
+    /// `name`.
+    pub fn try_c(&self, name: &str) -> SimResult<Self> {
         self.get_child(name)
-            .unwrap_or_else(|_| panic!("Could not get object with name {}.{}", self.name(), name))
     }
 
     pub fn release(&self) {
-        SIM_IF.release(self.handle).unwrap();
+        or_fail(self.try_release());
+    }
+
+    pub fn try_release(&self) -> SimResult<()> {
+        self.put(PutValue::Release, false)
     }
 
     pub fn set(&self, val: i32) {
         self._set(val, false);
     }
 
+    pub fn try_set(&self, val: i32) -> SimResult<()> {
+        self._try_set(val, false)
+    }
+
     pub fn force(&self, val: i32) {
         self._set(val, true);
     }
 
+    pub fn try_force(&self, val: i32) -> SimResult<()> {
+        self._try_set(val, true)
+    }
+
     #[inline]
     fn _set(&self, val: i32, force: bool) {
+        or_fail(self._try_set(val, force))
+    }
+
+    #[inline]
+    fn _try_set(&self, val: i32, force: bool) -> SimResult<()> {
         if !matches!(self.kind, ObjectKind::Bits) {
-            panic!(
-                "Can't set signal {} of kind {:?} using set() or set_u32()",
-                self.name(),
-                self.kind
-            );
+            return Err(self.kind_error("set"));
         }
-        SIM_IF.set_value_int(self.handle, val, force).unwrap();
+        self.put(PutValue::Int(val), force)
     }
 
     pub fn set_u32(&self, val: u32) {
         self._set_u32(val, false)
     }
 
+    pub fn try_set_u32(&self, val: u32) -> SimResult<()> {
+        // same bits, values from 1 << 31 on become negative
+        self._try_set(val as i32, false)
+    }
+
     pub fn force_u32(&self, val: u32) {
         self._set_u32(val, true)
     }
 
     #[inline]
     fn _set_u32(&self, val: u32, force: bool) {
-        if val >= 1 << 31 {
-            let val_i32: i32 = unsafe { std::mem::transmute(val) };
-            self._set(val_i32, force);
-        } else {
-            self._set(val as i32, force);
+        // same bits, values from 1 << 31 on become negative
+        self._set(val as i32, force);
+    }
+
+    pub fn set_u64(&self, val: u64) {
+        self._set_u128(val as u128, false)
+    }
+
+    pub fn try_set_u64(&self, val: u64) -> SimResult<()> {
+        self._try_set_bytes(&val.to_le_bytes(), false, "set_u64")
+    }
+
+    pub fn force_u64(&self, val: u64) {
+        self._set_u128(val as u128, true)
+    }
+
+    pub fn set_i64(&self, val: i64) {
+        self._set_u128(val as i128 as u128, false)
+    }
+
+    pub fn force_i64(&self, val: i64) {
+        self._set_u128(val as i128 as u128, true)
+    }
+
+    pub fn set_u128(&self, val: u128) {
+        self._set_u128(val, false)
+    }
+
+    pub fn force_u128(&self, val: u128) {
+        self._set_u128(val, true)
+    }
+
+    /// Sets a vector of any width from little endian bytes. Missing bytes are set to 0,
+    /// excess bits are ignored.
+    pub fn set_bytes(&self, val: &[u8]) {
+        self._set_bytes(val, false)
+    }
+
+    pub fn force_bytes(&self, val: &[u8]) {
+        self._set_bytes(val, true)
+    }
+
+    #[inline]
+    fn _set_u128(&self, val: u128, force: bool) {
+        self._set_bytes(&val.to_le_bytes(), force)
+    }
+
+    #[inline]
+    fn _set_bytes(&self, val: &[u8], force: bool) {
+        or_fail(self._try_set_bytes(val, force, "set_bytes"))
+    }
+
+    #[inline]
+    fn _try_set_bytes(&self, val: &[u8], force: bool, op: &'static str) -> SimResult<()> {
+        if !matches!(self.kind, ObjectKind::Bits) {
+            return Err(self.kind_error(op));
+        }
+        let size = self.size.max(1) as usize;
+        let mut words: Vec<VecWord> = (0..size.div_ceil(32))
+            .map(|i| {
+                let mut word = [0u8; 4];
+                for (j, b) in word.iter_mut().enumerate() {
+                    *b = val.get(4 * i + j).copied().unwrap_or(0);
+                }
+                VecWord {
+                    aval: u32::from_le_bytes(word),
+                    bval: 0,
+                }
+            })
+            .collect();
+        if !size.is_multiple_of(32) {
+            if let Some(last) = words.last_mut() {
+                last.aval &= (1 << (size % 32)) - 1;
+            }
+        }
+        self.put(PutValue::Vec(words), force)
+    }
+
+    pub fn set_f64(&self, val: f64) {
+        self._set_f64(val, false)
+    }
+
+    pub fn try_set_f64(&self, val: f64) -> SimResult<()> {
+        self._try_set_f64(val, false)
+    }
+
+    pub fn force_f64(&self, val: f64) {
+        self._set_f64(val, true)
+    }
+
+    #[inline]
+    fn _set_f64(&self, val: f64, force: bool) {
+        or_fail(self._try_set_f64(val, force))
+    }
+
+    #[inline]
+    fn _try_set_f64(&self, val: f64, force: bool) -> SimResult<()> {
+        if !matches!(self.kind, ObjectKind::Real) {
+            return Err(self.kind_error("set_f64"));
+        }
+        self.put(PutValue::Real(val), force)
+    }
+
+    pub fn set_string(&self, val: &str) {
+        self._set_string(val, false)
+    }
+
+    pub fn try_set_string(&self, val: &str) -> SimResult<()> {
+        self._try_set_string(val, false)
+    }
+
+    pub fn force_string(&self, val: &str) {
+        self._set_string(val, true)
+    }
+
+    #[inline]
+    fn _set_string(&self, val: &str, force: bool) {
+        or_fail(self._try_set_string(val, force))
+    }
+
+    #[inline]
+    fn _try_set_string(&self, val: &str, force: bool) -> SimResult<()> {
+        if !matches!(self.kind, ObjectKind::String) {
+            return Err(self.kind_error("set_string"));
+        }
+        self.put(PutValue::Str(val.to_string()), force)
+    }
+
+    /// Sets an enumeration typed signal to the literal `name`, compared ignoring case and
+    /// underscores.
+    pub fn set_enum_name(&self, name: &str) {
+        self._set_enum_name(name, false)
+    }
+
+    pub fn force_enum_name(&self, name: &str) {
+        self._set_enum_name(name, true)
+    }
+
+    pub fn set_enum<T: HdlEnum>(&self, val: T) {
+        self._set_enum_name(val.literal(), false)
+    }
+
+    pub fn force_enum<T: HdlEnum>(&self, val: T) {
+        self._set_enum_name(val.literal(), true)
+    }
+
+    #[inline]
+    fn _set_enum_name(&self, name: &str, force: bool) {
+        let val = self
+            .check_enum("set_enum")
+            .into_iter()
+            .find(|(lit, _)| same_literal(lit, name))
+            .map(|(_, v)| v)
+            .unwrap_or_else(|| panic!("{} has no enumeration literal {}.", self.name(), name));
+        or_fail(self.put(PutValue::Int(val as i32), force));
+    }
+
+    pub fn set_logic(&self, val: &LogicVec) {
+        self._set_logic(val, false)
+    }
+
+    pub fn try_set_logic(&self, val: &LogicVec) -> SimResult<()> {
+        self._try_set_logic(val, false)
+    }
+
+    pub fn force_logic(&self, val: &LogicVec) {
+        self._set_logic(val, true)
+    }
+
+    #[inline]
+    fn _set_logic(&self, val: &LogicVec, force: bool) {
+        or_fail(self._try_set_logic(val, force))
+    }
+
+    #[inline]
+    fn _try_set_logic(&self, val: &LogicVec, force: bool) -> SimResult<()> {
+        if !matches!(self.kind, ObjectKind::Bits) {
+            return Err(self.kind_error("set_logic"));
+        }
+        if val.width() != self.size as usize {
+            return Err(RstbError::InvalidValue {
+                op: "set_logic",
+                object: self.name(),
+                value: val.to_string(),
+            });
         }
+        // VPI only knows 4-state values
+        let bin = match self.interface {
+            Interface::Vpi => val.to_x01z().to_string().to_ascii_lowercase(),
+            _ => val.to_string(),
+        };
+        self.put(PutValue::Bin(bin), force)
     }
 
     pub fn set_bin(&self, val: &str) {
         self._set_bin(val, false)
     }
 
+    pub fn try_set_bin(&self, val: &str) -> SimResult<()> {
+        self._try_set_bin(val, false)
+    }
+
     pub fn force_bin(&self, val: &str) {
         self._set_bin(val, true)
     }
 
     #[inline]
     fn _set_bin(&self, val: &str, force: bool) {
+        or_fail(self._try_set_bin(val, force))
+    }
+
+    #[inline]
+    fn _try_set_bin(&self, val: &str, force: bool) -> SimResult<()> {
+        if !matches!(self.kind, ObjectKind::Bits) {
+            return Err(self.kind_error("set_bin"));
+        }
         // remove '_' and 0b
         let stripped = val.replace("0b", "");
         let stripped = stripped.replace("_", "");
-        if stripped.len() == self.size as usize {
-            let is_valid = stripped.chars().all(valid_char);
-            if is_valid {
-                SIM_IF.set_value_bin(self.handle, stripped, force).unwrap();
-            } else {
-                panic!("Can't set {} to {}. Invalid characters.", self.name(), val);
-            }
-        } else {
-            panic!("Can't set {} to {}. Length mismatch.", self.name(), val);
+        if stripped.len() != self.size as usize || !stripped.chars().all(valid_char) {
+            return Err(RstbError::InvalidValue {
+                op: "set_bin",
+                object: self.name(),
+                value: val.to_string(),
+            });
         }
+        self.put(PutValue::Bin(stripped), force)
     }
 
     // convenience functions to get edge triggers for this signal
@@ -239,7 +1294,535 @@ impl SimObject {
     }
 }
 
+// Value of a write, see `SimObject::put()`
+enum PutValue {
+    Int(i32),
+    Vec(Vec<VecWord>),
+    Bin(String),
+    Real(f64),
+    Str(String),
+    Release,
+}
+
+// Panics with the error's message, for the accessors that don't return a result
+#[inline]
+fn or_panic<T>(res: SimResult<T>) -> T {
+    res.unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Like `or_panic` for writes, but a write in the ReadOnly region fails the running test instead
+// of panicking inside a simulator callback
+fn or_fail(res: SimResult<()>) {
+    match res {
+        Err(e @ RstbError::ReadOnly { .. }) => fail_write(e),
+        res => or_panic(res),
+    }
+}
+
+// Queued and delayed writes are applied in simulator callbacks, where nobody could handle their
+// errors
+fn fail_write(e: RstbError) {
+    SIM_IF.log(&e.to_string());
+    crate::fail_test(&e.to_string());
+}
+
 fn valid_char(c: char) -> bool {
     let l = c.to_ascii_lowercase();
     l == '0' || l == '1' || l == 'z' || l == 'x'
 }
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use crate::prelude::*;
+
+    fn mem() -> MockSim {
+        MockSim::new("top").array("mem", 16, 8).signal("ctrl", 8)
+    }
+
+    #[test]
+    fn array_elements() {
+        let r = mem().run(|dut| {
+            async move {
+                let mem = dut.c("mem");
+                assert_eq!(mem.kind(), ObjectKind::Array);
+                assert_eq!((mem.len(), mem.range()), (16, (0, 15)));
+                assert_eq!(mem.elements().len(), 16);
+                assert_eq!(dut.arrays().len(), 1);
+                let vals: Vec<LogicVec> = (0..16).map(|i| LogicVec::from_u64(i * 3, 8)).collect();
+                mem.write_all(&vals);
+                let watcher = Task::spawn(async move {
+                    mem.index(5).edge().await;
+                    Ok(Val::Int(mem.index(5).u32()))
+                });
+                Trigger::timer(1, "ns").await;
+                assert_eq!(mem.read_all(), vals);
+                assert_eq!(watcher.await, Ok(Val::Int(15)));
+                assert_eq!(dut.c("mem[7]").u32(), 21);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    fn names(objects: Vec<SimObject>) -> Vec<String> {
+        objects.iter().map(|o| o.name()).collect()
+    }
+
+    #[test]
+    fn hierarchy() {
+        let sim = MockSim::new("top")
+            .signal("clk", 1)
+            .real("temp")
+            .array("mem", 4, 8)
+            .scope("core")
+            .input("core.a", 8)
+            .output("core.y", 8)
+            .signal("core.state", 2)
+            .scope("core.alu")
+            .param("core.W", 8);
+        let r = sim.run(|dut| {
+            async move {
+                let children = ["top.clk", "top.temp", "top.mem", "top.core"];
+                assert_eq!(names(dut.children()), children);
+                assert_eq!(names(dut.signals()), ["top.clk", "top.temp"]);
+                assert_eq!(names(dut.arrays()), ["top.mem"]);
+                assert_eq!(names(dut.instances()), ["top.core"]);
+                let core = dut.c("core");
+                assert_eq!(core.kind(), ObjectKind::Module);
+                let signals = ["top.core.a", "top.core.y", "top.core.state"];
+                assert_eq!(names(core.signals()), signals);
+                assert_eq!(names(core.instances()), ["top.core.alu"]);
+                assert!(core.scopes().is_empty());
+                let ports: Vec<(String, PortDirection)> =
+                    core.ports().into_iter().map(|(p, dir)| (p.name(), dir)).collect();
+                let expected = [
+                    ("top.core.a".to_string(), PortDirection::Input),
+                    ("top.core.y".to_string(), PortDirection::Output),
+                ];
+                assert_eq!(ports, expected);
+                assert!(dut.ports().is_empty());
+                assert!(core.c("alu").children().is_empty());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't write 17 values to array top.mem with 16 elements.")]
+    fn array_write_all_too_long() {
+        let _ = mem().run(|dut| {
+            async move {
+                dut.c("mem").write_all(&vec![LogicVec::zeros(8); 17]);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+    }
+
+    #[test]
+    fn bit_and_part_selects() {
+        let r = mem().run(|dut| {
+            async move {
+                let ctrl = dut.c("ctrl");
+                ctrl.set(0);
+                Trigger::timer(1, "ns").await;
+                let field = ctrl.slice(7, 4);
+                let bit = ctrl.bit(2);
+                assert_eq!((field.name(), field.size()), ("top.ctrl[7:4]".to_string(), 4));
+                assert_eq!(bit.name(), "top.ctrl[2]");
+                let on_bit = Task::spawn(async move {
+                    bit.rising_edge().await;
+                    Ok(Val::Int(ctrl.u32()))
+                });
+                let on_field = Task::spawn(async move {
+                    field.edge().await;
+                    Ok(Val::Int(ctrl.u32()))
+                });
+                Trigger::timer(1, "ns").await;
+                // read-modify-write of the parent vector
+                field.set(0xa);
+                Trigger::timer(1, "ns").await;
+                assert_eq!((ctrl.u32(), field.u32()), (0xa0, 0xa));
+                assert_eq!(on_field.await, Ok(Val::Int(0xa0)));
+                // bit 0 changes, the select on bit 2 must not fire
+                ctrl.bit(0).set(1);
+                Trigger::timer(1, "ns").await;
+                bit.set(1);
+                assert_eq!(on_bit.await, Ok(Val::Int(0xa5)));
+                // selects of selects use the indices of the outer select
+                assert_eq!(field.slice(2, 1).name(), "top.ctrl[6:5]");
+                assert_eq!(field.slice(2, 1).u32(), 1);
+                field.set_bin("x1z0");
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.bin(), "x1z00101");
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn params_of_instances() {
+        let sim = mem()
+            .param("WIDTH", 8)
+            .param_real("FREQ", 2.5)
+            .param_str("NAME", "dut")
+            .signal("core.x", 1)
+            .param("core.DEPTH", 1 << 40);
+        let r = sim.run(|dut| {
+            async move {
+                let params = dut.params();
+                assert_eq!(names(params.clone()), ["top.WIDTH", "top.FREQ", "top.NAME"]);
+                assert!(params.iter().all(|p| p.kind() == ObjectKind::Param));
+                assert_eq!(params[0].param_i64(), 8);
+                assert_eq!(params[1].param_real(), 2.5);
+                assert_eq!(params[2].param_str(), "dut");
+                // parameters are not listed as children, nor are those of nested instances
+                assert!(dut.children().iter().all(|c| c.kind() != ObjectKind::Param));
+                let core = dut.c("core");
+                assert_eq!(names(core.params()), ["top.core.DEPTH"]);
+                assert_eq!(core.params()[0].param_i64(), 1 << 40);
+                assert!(dut.c("ctrl").params().is_empty());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[derive(Debug, PartialEq, HdlEnum)]
+    enum State {
+        Idle,
+        ReadData,
+        #[hdl(name = "WRITE")]
+        Wr,
+    }
+
+    fn fsm() -> MockSim {
+        mem().enumeration("state", &["IDLE", "READ_DATA", "WRITE"])
+    }
+
+    #[test]
+    fn enum_accessors() {
+        let r = fsm().run(|dut| {
+            async move {
+                let state = dut.c("state");
+                let literals = state.enum_literals();
+                assert_eq!(literals[1], ("READ_DATA".to_string(), 1));
+                assert_eq!(state.enum_name(), "IDLE");
+                assert_eq!(state.enum_as::<State>(), State::Idle);
+                state.set_enum_name("read_data");
+                Trigger::read_only().await;
+                assert_eq!((state.enum_name(), state.u32()), ("READ_DATA".to_string(), 1));
+                assert_eq!(state.enum_as::<State>(), State::ReadData);
+                Trigger::timer(1, "ns").await;
+                state.set_enum(State::Wr);
+                Trigger::read_only().await;
+                assert_eq!(state.enum_name(), "WRITE");
+                // values without a literal are returned as binary string
+                Trigger::timer(1, "ns").await;
+                state.set(3);
+                Trigger::read_only().await;
+                assert_eq!(state.enum_name(), "11");
+                Trigger::timer(1, "ns").await;
+                state.set_bin("x1");
+                Trigger::read_only().await;
+                assert_eq!(state.enum_name(), "x1");
+                assert!(dut.c("ctrl").enum_literals().is_empty());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    #[should_panic(expected = "top.state has no enumeration literal BUSY.")]
+    fn unknown_enum_literal() {
+        let _ = fsm().run(|dut| {
+            async move {
+                dut.c("state").set_enum_name("BUSY");
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "top.state holds 11, which is no variant of")]
+    fn enum_value_without_variant() {
+        let _ = fsm().run(|dut| {
+            async move {
+                dut.c("state").set(3);
+                Trigger::read_only().await;
+                dut.c("state").enum_as::<State>();
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't use enum_name() on top.ctrl, which is not enumeration typed.")]
+    fn enum_name_on_vector() {
+        let _ = fsm().run(|dut| {
+            async move {
+                dut.c("ctrl").enum_name();
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+    }
+
+    #[test]
+    fn select_writes_to_same_parent() {
+        let r = mem().run(|dut| {
+            async move {
+                let ctrl = dut.c("ctrl");
+                ctrl.set(0);
+                Trigger::timer(1, "ns").await;
+                // both are queued, the second one must not read the parent before the first
+                ctrl.slice(7, 4).set(0xa);
+                ctrl.slice(3, 0).set(0x5);
+                Trigger::read_write().await;
+                assert_eq!(ctrl.u32(), 0);
+                // the simulator shows writes of the ReadWrite region only once it returns
+                ctrl.bit(7).set(0);
+                ctrl.bit(0).set(0);
+                Trigger::read_only().await;
+                assert_eq!(ctrl.u32(), 0x24);
+                Trigger::timer(1, "ns").await;
+                // a write to the whole vector followed by a select
+                ctrl.set(0xff);
+                ctrl.bit(4).set(0);
+                Trigger::read_only().await;
+                assert_eq!(ctrl.u32(), 0xef);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not get object with name top.ctrl[8:4]")]
+    fn select_out_of_range() {
+        let _ = mem().run(|dut| {
+            async move {
+                dut.c("ctrl").slice(8, 4);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't use index() on top.ctrl")]
+    fn index_on_vector() {
+        let _ = mem().run(|dut| {
+            async move {
+                dut.c("ctrl").index(0);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+    }
+
+    #[test]
+    fn values_by_kind() {
+        let sim = mem().signal("wide", 40).real("r").string("s");
+        let r = sim.run(|dut| {
+            async move {
+                let ctrl = dut.c("ctrl");
+                ctrl.set_bin("0000000x");
+                dut.c("wide").set_u64(3);
+                dut.c("r").set_f64(1.5);
+                dut.c("s").set_string("abc");
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.value(), Val::Logic("0000000X".parse().unwrap()));
+                assert_eq!(dut.c("wide").value(), Val::Logic(LogicVec::from_u64(3, 40)));
+                assert_eq!(dut.c("r").value(), Val::Float(1.5));
+                assert_eq!(dut.c("s").value(), Val::String("abc".to_string()));
+                ctrl.set(0x81);
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.value(), Val::Int(0x81));
+                assert_eq!(ctrl.slice(3, 0).value(), Val::Int(1));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn write_modes_and_delays() {
+        let sim = mem().signal("wide", 40).real("r");
+        let r = sim.run(|dut| {
+            async move {
+                let ctrl = dut.c("ctrl");
+                ctrl.with_mode(WriteMode::NoDelay).set(7);
+                assert_eq!(ctrl.u32(), 7);
+                ctrl.set_after(1, 5, "ns");
+                ctrl.with_mode(WriteMode::Transport).set_after(2, 10, "ns");
+                dut.c("wide").set_u64_after(1 << 36, 5, "ns");
+                dut.c("r").set_f64_after(0.5, 5, "ns");
+                Trigger::timer(6, "ns").await;
+                assert_eq!(ctrl.u32(), 1);
+                assert_eq!(dut.c("wide").u64(), 1 << 36);
+                assert_eq!(dut.c("r").f64(), 0.5);
+                Trigger::timer(5, "ns").await;
+                assert_eq!(ctrl.u32(), 2);
+                // an inertial write discards the pending ones
+                ctrl.set_bin_after("0000xxxx", 5, "ns");
+                ctrl.with_mode(WriteMode::Inertial)
+                    .set_logic_after(&LogicVec::from_u64(4, 8), 10, "ns");
+                Trigger::timer(6, "ns").await;
+                assert_eq!(ctrl.u32(), 2);
+                Trigger::timer(5, "ns").await;
+                assert_eq!(ctrl.u32(), 4);
+                // but keeps transport delayed writes
+                ctrl.with_mode(WriteMode::Transport).set_after(9, 5, "ns");
+                ctrl.with_mode(WriteMode::Inertial).set_after(3, 2, "ns");
+                Trigger::timer(3, "ns").await;
+                assert_eq!(ctrl.u32(), 3);
+                Trigger::timer(3, "ns").await;
+                assert_eq!(ctrl.u32(), 9);
+                assert!(unsafe { super::PENDING_WRITES.is_empty() });
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn write_in_read_only_fails_test() {
+        let r = mem().run(|dut| {
+            async move {
+                let ctrl = dut.c("ctrl");
+                Trigger::read_only().await;
+                let msg = "Can't write top.ctrl in the ReadOnly synchronization region. \
+                           Await a timer or edge first.";
+                let err = ctrl.try_set(1).unwrap_err();
+                assert_eq!(err, RstbError::ReadOnly { object: "top.ctrl".to_string() });
+                assert_eq!(err.to_string(), msg);
+                // delayed writes are applied later, outside of the ReadOnly region
+                ctrl.set_after(2, 1, "ns");
+                ctrl.set(1);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert_eq!(
+            r,
+            Err(Val::String(
+                "Can't write top.ctrl in the ReadOnly synchronization region. \
+                 Await a timer or edge first."
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn try_accessors() {
+        let sim = mem().signal("big", 130).real("r").param("p", -5).param_str("s", "abc");
+        let r = sim.run(|dut| {
+            async move {
+                let (mem, ctrl) = (dut.c("mem"), dut.c("ctrl"));
+                let (big, r) = (dut.c("big"), dut.c("r"));
+                let not_found = |name: &str| RstbError::NotFound { name: name.to_string() };
+                let kind = |op, object: &SimObject| RstbError::Kind {
+                    op,
+                    object: object.name(),
+                    kind: object.kind(),
+                };
+                assert_eq!(mem.try_index(16).unwrap_err(), not_found("top.mem[16]"));
+                assert_eq!(ctrl.try_index(0).unwrap_err(), kind("index", &ctrl));
+                assert_eq!(ctrl.try_bit(8).unwrap_err(), not_found("top.ctrl[8]"));
+                assert_eq!(ctrl.try_slice(3, 4).unwrap_err(), not_found("top.ctrl[3:4]"));
+                assert_eq!(mem.try_bit(0).unwrap_err(), kind("bit", &mem));
+                assert_eq!(ctrl.try_slice(3, 0).unwrap().size(), 4);
+                assert_eq!(big.try_i64().unwrap_err(), kind("i64", &big));
+                assert_eq!(big.try_u128().unwrap_err(), kind("u128", &big));
+                assert_eq!(r.try_bytes().unwrap_err(), kind("bytes", &r));
+                assert_eq!(r.try_logic().unwrap_err(), kind("get_value_bin", &r));
+                assert_eq!(ctrl.try_param_i64().unwrap_err(), kind("param_i64", &ctrl));
+                assert_eq!(dut.c("p").try_param_i64(), Ok(-5));
+                assert_eq!(dut.c("s").try_param_str(), Ok("abc".to_string()));
+                assert_eq!(r.try_param_real().unwrap_err(), kind("param_real", &r));
+                // writes are checked when they are queued
+                assert_eq!(r.try_set(1).unwrap_err(), kind("set", &r));
+                assert_eq!(ctrl.try_set_f64(1.0).unwrap_err(), kind("set_f64", &ctrl));
+                assert_eq!(
+                    ctrl.try_set_bin("101").unwrap_err(),
+                    RstbError::InvalidValue {
+                        op: "set_bin",
+                        object: "top.ctrl".to_string(),
+                        value: "101".to_string(),
+                    }
+                );
+                ctrl.try_set_bin("1010_zzzz").unwrap();
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.try_logic(), Ok("1010ZZZZ".parse().unwrap()));
+                assert_eq!(ctrl.try_i64(), Ok(-96));
+                assert_eq!(ctrl.try_bytes(), Ok(vec![0xa0]));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn try_setters() {
+        let r = mem().signal("big", 40).real("r").run(|dut| {
+            async move {
+                let (ctrl, big, r) = (dut.c("ctrl"), dut.c("big"), dut.c("r"));
+                let kind = |op, object: &SimObject| RstbError::Kind {
+                    op,
+                    object: object.name(),
+                    kind: object.kind(),
+                };
+                assert_eq!(r.try_set_u32(1).unwrap_err(), kind("set", &r));
+                assert_eq!(r.try_set_u64(1).unwrap_err(), kind("set_u64", &r));
+                assert_eq!(r.try_force(1).unwrap_err(), kind("set", &r));
+                let logic: LogicVec = "10XZ".parse().unwrap();
+                assert_eq!(r.try_set_logic(&logic).unwrap_err(), kind("set_logic", &r));
+                assert_eq!(
+                    ctrl.try_set_logic(&logic).unwrap_err(),
+                    RstbError::InvalidValue {
+                        op: "set_logic",
+                        object: "top.ctrl".to_string(),
+                        value: "10XZ".to_string(),
+                    }
+                );
+                ctrl.try_set_u32(0xa5).unwrap();
+                big.try_set_u64(0xff_0000_0001).unwrap();
+                Trigger::timer(1, "ns").await;
+                assert_eq!((ctrl.u32(), big.u64()), (0xa5, 0xff_0000_0001));
+                ctrl.try_set_logic(&"1010XXZZ".parse().unwrap()).unwrap();
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.try_logic(), Ok("1010XXZZ".parse().unwrap()));
+                ctrl.try_force(3).unwrap();
+                ctrl.set(4);
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.u32(), 3);
+                ctrl.try_release().unwrap();
+                ctrl.set(4);
+                Trigger::timer(1, "ns").await;
+                assert_eq!(ctrl.u32(), 4);
+                Trigger::read_only().await;
+                let read_only = RstbError::ReadOnly { object: "top.ctrl".to_string() };
+                assert_eq!(ctrl.try_force(1).unwrap_err(), read_only);
+                assert_eq!(ctrl.try_release().unwrap_err(), read_only);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
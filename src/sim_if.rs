@@ -1,22 +1,116 @@
-use crate::SimpleResult;
+use crate::SimResult;
+use num_format::{Locale, ToFormattedString};
 
-#[cfg(feature = "vhpi")]
+#[cfg(feature = "mock")]
+use crate::mock;
+#[cfg(all(feature = "vhpi", not(feature = "mock")))]
 use crate::vhpi;
-#[cfg(feature = "vpi")]
+#[cfg(all(feature = "vpi", not(feature = "mock")))]
 use crate::vpi;
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
 
+// The primary interface is the one through which the simulator loaded rstb. It schedules
+// timers and ReadWrite/ReadOnly synchronization. In mixed-language simulations objects
+// may be owned by another interface, which then handles their values and edge callbacks.
+static PRIMARY: OnceCell<Interface> = OnceCell::new();
+
+lazy_static! {
+    pub static ref SIM_IF: &'static (dyn SimIf + Sync) = get_interface(primary_interface());
+}
+
+#[cfg(all(feature = "vpi", not(feature = "mock")))]
 lazy_static! {
-    pub static ref SIM_IF: Box<dyn SimIf + Sync> = new_interface();
+    static ref VPI: vpi::Vpi = vpi::Vpi::new();
+}
+#[cfg(all(feature = "vhpi", not(feature = "mock")))]
+lazy_static! {
+    static ref VHPI: vhpi::Vhpi = vhpi::Vhpi::new();
+}
+
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Interface {
+    Vpi,
+    Vhpi,
+    Mock,
 }
 
-#[cfg(feature = "vpi")]
-fn new_interface() -> Box<dyn SimIf + Sync> {
-    Box::new(vpi::Vpi::new())
+/// Simulators rstb knows about, detected from the product name the simulator reports
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Simulator {
+    Questa,
+    Icarus,
+    Xcelium,
+    Riviera,
+    Vcs,
+    Verilator,
+    Ghdl,
+    Nvc,
+    Mock,
+    Unknown,
 }
-#[cfg(feature = "vhpi")]
-fn new_interface() -> Box<dyn SimIf + Sync> {
-    Box::new(vhpi::Vhpi {})
+
+impl Simulator {
+    pub fn from_product(product: &str) -> Self {
+        let product = product.to_ascii_lowercase();
+        let known = [
+            ("questa", Simulator::Questa),
+            ("modelsim", Simulator::Questa),
+            ("icarus", Simulator::Icarus),
+            ("xcelium", Simulator::Xcelium),
+            ("xmsim", Simulator::Xcelium),
+            ("ncsim", Simulator::Xcelium),
+            ("riviera", Simulator::Riviera),
+            ("vcs", Simulator::Vcs),
+            ("verilator", Simulator::Verilator),
+            ("ghdl", Simulator::Ghdl),
+            ("nvc", Simulator::Nvc),
+            ("mock", Simulator::Mock),
+        ];
+        known
+            .iter()
+            .find(|(name, _)| product.contains(name))
+            .map(|(_, sim)| *sim)
+            .unwrap_or(Simulator::Unknown)
+    }
+}
+
+// Interfaces enabled in this build, in the order in which they are searched for objects.
+#[cfg(feature = "mock")]
+const INTERFACES: &[Interface] = &[Interface::Mock];
+#[cfg(all(feature = "vpi", feature = "vhpi", not(feature = "mock")))]
+const INTERFACES: &[Interface] = &[Interface::Vpi, Interface::Vhpi];
+#[cfg(all(feature = "vpi", not(feature = "vhpi"), not(feature = "mock")))]
+const INTERFACES: &[Interface] = &[Interface::Vpi];
+#[cfg(all(feature = "vhpi", not(feature = "vpi"), not(feature = "mock")))]
+const INTERFACES: &[Interface] = &[Interface::Vhpi];
+
+pub(crate) fn set_primary_interface(interface: Interface) {
+    // only the first interface to load rstb becomes primary
+    let _ = PRIMARY.set(interface);
+}
+
+pub fn primary_interface() -> Interface {
+    *PRIMARY.get().unwrap_or(&INTERFACES[0])
+}
+
+/// Interfaces that are searched for objects, starting with the primary interface.
+pub(crate) fn interfaces() -> impl Iterator<Item = Interface> {
+    let primary = primary_interface();
+    std::iter::once(primary).chain(INTERFACES.iter().copied().filter(move |i| *i != primary))
+}
+
+pub fn get_interface(interface: Interface) -> &'static (dyn SimIf + Sync) {
+    match interface {
+        #[cfg(all(feature = "vpi", not(feature = "mock")))]
+        Interface::Vpi => &*VPI,
+        #[cfg(all(feature = "vhpi", not(feature = "mock")))]
+        Interface::Vhpi => &*VHPI,
+        #[cfg(feature = "mock")]
+        Interface::Mock => &mock::Mock,
+        #[allow(unreachable_patterns)]
+        _ => panic!("Interface {:?} is not enabled in this build.", interface),
+    }
 }
 
 #[derive(Debug, Hash, Clone, Eq, PartialEq)]
@@ -25,6 +119,9 @@ pub enum SimCallback {
     Edge(usize),
     ReadWrite,
     ReadOnly,
+    NextTimeStep,
+    StartOfTimeStep(u64),
+    Nba,
 }
 
 pub enum ObjectValue {
@@ -34,35 +131,184 @@ pub enum ObjectValue {
     Other,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ObjectKind {
     Bits,
     Real,
+    String,
     Array,
+    // module / entity instance
+    Module,
+    // generate scope, named block
+    Scope,
+    // parameter, generic or constant
+    Param,
     Other,
 }
 
-#[allow(clippy::result_unit_err)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+    Inout,
+    Other,
+}
+
+/// How values are written to the simulator. Delays of writes, see `SimObject::after`, are
+/// emulated with rstb timers for every mode, the simulator itself always gets a zero delay. So
+/// `Inertial` and `Transport` only differ in which pending delayed writes rstb keeps.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Applied in the next ReadWrite synchronization region. VPI schedules the value as an event
+    /// in the current time step, like a testbench assignment (`vpiInertialDelay` with zero
+    /// delay, as cocotb does). VHPI deposits the value and propagates it. Pending delayed writes
+    /// to the object are kept.
+    #[default]
+    Deposit,
+    /// Applied immediately, without scheduling an event and without waiting for the ReadWrite
+    /// synchronization region (VPI `vpiNoDelay`, VHPI `vhpiDeposit`)
+    NoDelay,
+    /// Like `Deposit`, but a write discards the object's pending delayed writes, like an inertial
+    /// delay in the HDL. Pending transport delayed writes are kept.
+    Inertial,
+    /// Like `Deposit`, but a delayed write is never discarded by later writes, like a transport
+    /// delay in the HDL. VPI gets `vpiTransportDelay`.
+    Transport,
+    /// Overrides the driven value until released
+    Force,
+}
+
+/// Simulation control commands, like the `$stop`, `$finish` and `$reset` system tasks
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SimControl {
+    Stop,
+    Finish,
+    Reset,
+}
+
+/// 32 bit word of a four-state vector value, encoded like VPI's `s_vpi_vecval`:
+/// 0 = (aval 0, bval 0), 1 = (1, 0), Z = (0, 1), X = (1, 1)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VecWord {
+    pub aval: u32,
+    pub bval: u32,
+}
+
+pub(crate) fn parse_plusargs(args: &[String]) -> Vec<(String, String)> {
+    args.iter()
+        .filter_map(|arg| arg.strip_prefix('+'))
+        .map(|arg| match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (arg.to_string(), String::new()),
+        })
+        .collect()
+}
+
+// Converts a binary string (MSB first) to vector words (least significant word first).
+pub(crate) fn bin_to_vec(bin: &str) -> Vec<VecWord> {
+    let mut words = vec![VecWord::default(); bin.len().div_ceil(32)];
+    for (i, c) in bin.chars().rev().enumerate() {
+        let (a, b) = match c {
+            '0' | 'L' | 'l' => (0, 0),
+            '1' | 'H' | 'h' => (1, 0),
+            'Z' | 'z' => (0, 1),
+            _ => (1, 1),
+        };
+        words[i / 32].aval |= a << (i % 32);
+        words[i / 32].bval |= b << (i % 32);
+    }
+    words
+}
+
+// Converts vector words (least significant word first) to a binary string of `size` bits.
+pub(crate) fn vec_to_bin(words: &[VecWord], size: usize) -> String {
+    (0..size)
+        .rev()
+        .map(|i| {
+            let w = words.get(i / 32).copied().unwrap_or_default();
+            match ((w.aval >> (i % 32)) & 1, (w.bval >> (i % 32)) & 1) {
+                (0, 0) => '0',
+                (1, 0) => '1',
+                (0, _) => 'z',
+                _ => 'x',
+            }
+        })
+        .collect()
+}
+
 pub trait SimIf {
-    fn set_value_int(&self, handle: usize, value: i32, force: bool) -> SimpleResult<()>;
-    fn get_value_int(&self, obj: usize) -> SimpleResult<i32>;
-    fn set_value_bin(&self, obj: usize, value: String, force: bool) -> SimpleResult<()>;
-    fn get_value_bin(&self, obj: usize) -> SimpleResult<String>;
-    fn release(&self, obj: usize) -> SimpleResult<()>;
-    fn get_handle_by_name(&self, name: &str) -> SimpleResult<usize>;
+    fn set_value_int(&self, handle: usize, value: i32, mode: WriteMode) -> SimResult<()>;
+    fn get_value_int(&self, obj: usize) -> SimResult<i32>;
+    fn set_value_bin(&self, obj: usize, value: String, mode: WriteMode) -> SimResult<()>;
+    fn get_value_bin(&self, obj: usize) -> SimResult<String>;
+    fn set_value_f64(&self, obj: usize, value: f64, mode: WriteMode) -> SimResult<()>;
+    fn get_value_f64(&self, obj: usize) -> SimResult<f64>;
+    fn set_value_str(&self, obj: usize, value: &str, mode: WriteMode) -> SimResult<()>;
+    fn get_value_str(&self, obj: usize) -> SimResult<String>;
+    fn release(&self, obj: usize) -> SimResult<()>;
+    // Vector values of any width. Interfaces without a native vector format go through binary strings.
+    fn set_value_vec(&self, obj: usize, value: &[VecWord], mode: WriteMode) -> SimResult<()> {
+        let size = self.get_size(obj) as usize;
+        self.set_value_bin(obj, vec_to_bin(value, size), mode)
+    }
+    fn get_value_vec(&self, obj: usize) -> SimResult<Vec<VecWord>> {
+        Ok(bin_to_vec(&self.get_value_bin(obj)?))
+    }
+    fn get_handle_by_name(&self, name: &str) -> SimResult<usize>;
     fn get_sim_time_steps(&self) -> u64;
     fn log(&self, s: &str);
     fn get_size(&self, obj_handle: usize) -> i32;
     fn get_kind(&self, obj_handle: usize) -> ObjectKind;
     fn is_signed(&self, obj_handle: usize) -> bool;
-    fn get_full_name(&self, obj: usize) -> SimpleResult<String>;
+    fn get_full_name(&self, obj: usize) -> SimResult<String>;
     fn get_sim_precision(&self) -> i8;
-    fn get_root_handle(&self) -> SimpleResult<usize>;
-    fn register_callback_rw(&self) -> SimpleResult<usize>;
-    fn register_callback_ro(&self) -> SimpleResult<usize>;
-    fn register_callback_time(&self, t: u64) -> SimpleResult<usize>;
-    fn register_callback_edge(&self, sig_hdl: usize) -> SimpleResult<usize>;
-    fn cancel_callback(&self, cb_hdl: usize) -> SimpleResult<()>;
+    fn get_root_handle(&self) -> SimResult<usize>;
+    // Handles of signals, arrays, instances and scopes directly below `obj`. May contain duplicates.
+    fn get_children(&self, obj: usize) -> Vec<usize>;
+    // Handles of the signals connected to the ports of instance `obj`, seen from inside.
+    fn get_ports(&self, obj: usize) -> Vec<(usize, PortDirection)>;
+    // Handles of the parameters, generics and constants directly below `obj`
+    fn get_params(&self, obj: usize) -> Vec<usize>;
+    // Literals of the enumeration type of `obj` with their values (VPI) or positions (VHPI)
+    fn get_enum_literals(&self, obj: usize) -> Vec<(String, i64)>;
+    // Element of array `obj` at HDL index `index`
+    fn get_handle_by_index(&self, obj: usize, index: i32) -> SimResult<usize>;
+    // Left and right bound of array `obj`
+    fn get_range(&self, obj: usize) -> SimResult<(i32, i32)>;
+    fn register_callback_rw(&self) -> SimResult<usize>;
+    fn register_callback_ro(&self) -> SimResult<usize>;
+    fn register_callback_time(&self, t: u64) -> SimResult<usize>;
+    fn register_callback_edge(&self, sig_hdl: usize) -> SimResult<usize>;
+    // Start of the next time step in which anything happens
+    fn register_callback_next_time_step(&self) -> SimResult<usize>;
+    // Start of the time step `t` steps from now, before any of its events
+    fn register_callback_start_of_time_step(&self, t: u64) -> SimResult<usize>;
+    // Before nonblocking assignments are updated (VHPI: after all processes have run)
+    fn register_callback_nba(&self) -> SimResult<usize>;
+    fn cancel_callback(&self, cb_hdl: usize) -> SimResult<()>;
+    // Takes effect once the current callback returns
+    fn control(&self, command: SimControl) -> SimResult<()>;
+    /// Command line arguments of the simulator, starting with the executable
+    fn args(&self) -> Vec<String>;
+    /// Product name reported by the simulator
+    fn product(&self) -> String;
+    /// Version reported by the simulator
+    fn version(&self) -> String;
+    fn simulator(&self) -> Simulator {
+        Simulator::from_product(&self.product())
+    }
+    /// All plusargs as name/value pairs: `+SEED=5` gives `("SEED", "5")`, `+VERBOSE` gives
+    /// `("VERBOSE", "")`
+    fn plusargs(&self) -> Vec<(String, String)> {
+        parse_plusargs(&self.args())
+    }
+    /// Value of the first plusarg called `name`
+    fn plusarg(&self, name: &str) -> Option<String> {
+        self.plusargs()
+            .into_iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
     fn get_sim_time(&self, unit: &str) -> u64 {
         let precision = self.get_sim_precision();
         let scale = time_scale(unit);
@@ -71,7 +317,7 @@ pub trait SimIf {
         }
         let steps = self.get_sim_time_steps();
         let steps_per_unit = 10_u64.pow((scale-precision) as u32);
-        if steps % steps_per_unit != 0 {
+        if !steps.is_multiple_of(steps_per_unit) {
             panic!("Current simulation time cannot accurately be converted to specified precision.")
         }
         steps / steps_per_unit
@@ -98,6 +344,13 @@ pub trait SimIf {
     }
 }
 
+// Formats a simulation time in ns for log output, e.g. "1,234.500ns"
+pub(crate) fn time_stamp(t_ns: f64) -> String {
+    // round to ps first, so e.g. 1.9999 carries into the integer part
+    let ps = (t_ns * 1000.0).round() as u64;
+    format!("{}.{:03}ns", (ps / 1000).to_formatted_string(&Locale::en), ps % 1000)
+}
+
 fn time_scale(unit: &str) -> i8 {
     match unit {
         "fs" => -15,
@@ -131,3 +384,70 @@ fn ldexp10(frac: f64, exp: i8) -> f64 {
         frac / div
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn plusargs() {
+        let parsed = parse_plusargs(&args(&[
+            "vsim",
+            "-c",
+            "+VERBOSE",
+            "+SEED=5",
+            "top",
+            "+SEED=7",
+            "+PATH=a=b",
+            "+",
+        ]));
+        let expected = [
+            ("VERBOSE", ""),
+            ("SEED", "5"),
+            ("SEED", "7"),
+            ("PATH", "a=b"),
+            ("", ""),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(parsed, expected);
+        assert!(parse_plusargs(&args(&["sim", "-gSEED=5"])).is_empty());
+    }
+
+    #[test]
+    fn simulator_from_product() {
+        let products = [
+            ("Questa Sim-64", Simulator::Questa),
+            ("ModelSim ALTERA STARTER EDITION", Simulator::Questa),
+            ("Icarus Verilog", Simulator::Icarus),
+            ("Xcelium", Simulator::Xcelium),
+            ("xmsim(64)", Simulator::Xcelium),
+            ("ncsim(64)", Simulator::Xcelium),
+            ("Riviera-PRO", Simulator::Riviera),
+            ("Chronologic Simulation VCS Release", Simulator::Vcs),
+            ("Verilator", Simulator::Verilator),
+            ("GHDL", Simulator::Ghdl),
+            ("nvc", Simulator::Nvc),
+            ("rstb mock", Simulator::Mock),
+            ("Some Simulator", Simulator::Unknown),
+            ("", Simulator::Unknown),
+        ];
+        for (product, simulator) in products {
+            assert_eq!(Simulator::from_product(product), simulator, "{}", product);
+        }
+    }
+
+    #[test]
+    fn time_stamp_rounding() {
+        assert_eq!(time_stamp(0.0), "0.000ns");
+        assert_eq!(time_stamp(1234.5), "1,234.500ns");
+        assert_eq!(time_stamp(1.9999), "2.000ns");
+        assert_eq!(time_stamp(999.9996), "1,000.000ns");
+    }
+}
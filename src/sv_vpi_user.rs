@@ -1,6 +1,11 @@
 /* automatically generated by rust-bindgen 0.58.1 */
 
+pub const vpiTypespec: u32 = 605;
 pub const vpiLongIntVar: u32 = 610;
 pub const vpiIntVar: u32 = 612;
 pub const vpiShortRealVar: u32 = 613;
+pub const vpiStringVar: u32 = 616;
+pub const vpiEnumVar: u32 = 617;
 pub const vpiBitVar: u32 = 620;
+pub const vpiEnumConst: u32 = 634;
+pub const vpiEnumNet: u32 = 680;
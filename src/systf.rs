@@ -0,0 +1,225 @@
+// User defined system tasks and functions. HDL code calls them like built-in ones, e.g.
+// `$rstb_event("done")` or `x = $rstb_model(a, b)`, and rstb executes the Rust closure
+// registered for them.
+
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::rstb_obj::RstbObjSafe;
+use crate::sim_if::SIM_IF;
+use crate::trigger;
+use crate::value::Val;
+use crate::{RstbError, SimResult};
+
+/// Kind of a system task or function. Functions return their closure's value with the given
+/// type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SysTfKind {
+    Task,
+    /// 32 bit integer
+    IntFunc,
+    RealFunc,
+    /// Vector of the given width
+    SizedFunc(i32),
+}
+
+type SysTfFn = Arc<dyn Fn(&[Val]) -> Val + Send + Sync>;
+
+struct SysTf {
+    name: String,
+    kind: SysTfKind,
+    f: SysTfFn,
+}
+
+lazy_static! {
+    // the index is passed to the simulator to identify the system task
+    static ref SYSTFS: RstbObjSafe<Vec<SysTf>> = RstbObjSafe::new(Vec::new());
+}
+// Set once the system tasks have been registered with the simulator. Later ones are registered
+// right away.
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Registers the system task or function `name`, which must start with '$'. HDL calls execute
+/// `f` with the arguments of the call, functions return its result.
+///
+/// Most simulators only accept system tasks while loading rstb, so they should be registered
+/// in the `setup` function of `run_with_vpi!` or `run_with_vhpi!`. Only supported through VPI,
+/// in mixed-language simulations they are registered once rstb is loaded through VPI.
+///
+/// Registering `name` again replaces its closure. Returns `RstbError::InvalidValue` if `kind`
+/// differs from the first registration, or the simulator's error if it rejects the system task.
+pub fn register_systf(
+    name: &str,
+    kind: SysTfKind,
+    f: impl Fn(&[Val]) -> Val + Send + Sync + 'static,
+) -> SimResult<()> {
+    if !name.starts_with('$') {
+        panic!("System task name {} must start with '$'.", name);
+    }
+    let f: SysTfFn = Arc::new(f);
+    let id = SYSTFS.with_mut(|mut systfs| {
+        // the setup function is executed again if rstb is loaded through VPI and VHPI
+        if let Some(systf) = systfs.iter_mut().find(|s| s.name == name) {
+            // the simulator keeps the kind it was registered with
+            if systf.kind != kind {
+                return Err(RstbError::InvalidValue {
+                    op: "register_systf",
+                    object: name.to_string(),
+                    value: format!("{:?}", kind),
+                });
+            }
+            systf.f = f;
+            return Ok(None);
+        }
+        systfs.push(SysTf {
+            name: name.to_string(),
+            kind,
+            f,
+        });
+        Ok(Some(systfs.len() - 1))
+    })?;
+    match id {
+        Some(id) if REGISTERED.load(Ordering::Relaxed) => register(name, kind, id),
+        _ => Ok(()),
+    }
+}
+
+// Runs while rstb is loaded, before any test could fail, so errors are only logged.
+pub(crate) fn register_all() {
+    REGISTERED.store(true, Ordering::Relaxed);
+    let systfs: Vec<(String, SysTfKind)> = SYSTFS
+        .get()
+        .iter()
+        .map(|s| (s.name.clone(), s.kind))
+        .collect();
+    for (id, (name, kind)) in systfs.iter().enumerate() {
+        if let Err(e) = register(name, *kind, id) {
+            SIM_IF.log(&e.to_string());
+        }
+    }
+}
+
+fn register(name: &str, kind: SysTfKind, id: usize) -> SimResult<()> {
+    #[cfg(feature = "mock")]
+    {
+        crate::mock::register_systf(name, kind, id)
+    }
+    #[cfg(all(feature = "vpi", not(feature = "mock")))]
+    {
+        crate::vpi::register_systf(name, kind, id)
+    }
+    #[cfg(not(any(feature = "vpi", feature = "mock")))]
+    {
+        let _ = (kind, id);
+        Err(crate::RstbError::sim(
+            "register_systf",
+            name,
+            Some("system tasks are only supported through VPI".to_string()),
+        ))
+    }
+}
+
+pub(crate) fn is_registered(name: &str) -> bool {
+    SYSTFS.get().iter().any(|s| s.name == name)
+}
+
+#[cfg(feature = "vpi")]
+pub(crate) fn kind(id: usize) -> SysTfKind {
+    SYSTFS.get()[id].kind
+}
+
+// Executes a call from HDL code, then wakes the tasks awaiting it.
+pub(crate) fn call(id: usize, args: Vec<Val>) -> Val {
+    let (name, f) = {
+        let systfs = SYSTFS.get();
+        (systfs[id].name.clone(), systfs[id].f.clone())
+    };
+    let ret = f(&args);
+    trigger::react_call(&name, args);
+    ret
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn add(args: &[Val]) -> Val {
+        let sum = args.iter().fold(0, |acc, a| match a {
+            Val::Int(v) => acc + v,
+            _ => acc,
+        });
+        Val::Int(sum)
+    }
+
+    #[test]
+    fn calls_from_hdl() {
+        register_systf("$rstb_add", SysTfKind::IntFunc, add).unwrap();
+        register_systf("$rstb_event", SysTfKind::Task, |_| Val::None).unwrap();
+        let sim = MockSim::new("top")
+            .signal("a", 8)
+            .signal("sum", 8)
+            .process(&["a"], |dut| {
+                let a = Val::Int(dut.c("a").u32());
+                if let Val::Int(sum) = MockSim::call_systf("$rstb_add", &[a.clone(), Val::Int(1)]) {
+                    dut.c("sum").set_u32(sum);
+                }
+                if a == Val::Int(3) {
+                    MockSim::call_systf("$rstb_event", &[Val::String("done".to_string())]);
+                }
+            });
+        let r = sim.run(|dut| {
+            async move {
+                let event = Task::spawn(async { Ok(Trigger::systf("$rstb_event").await) });
+                let add = Task::spawn(async { Ok(Trigger::systf("$rstb_add").await) });
+                dut.c("a").set(2);
+                Trigger::timer(1, "ns").await;
+                assert_eq!(dut.c("sum").u32(), 3);
+                assert_eq!(add.await, Ok(Val::Vec(vec![Val::Int(2), Val::Int(1)])));
+                dut.c("a").set(3);
+                let args = event.await;
+                assert_eq!(args, Ok(Val::Vec(vec![Val::String("done".to_string())])));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn unregistered_trigger_fails_test() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                Trigger::systf("$rstb_unknown").await;
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        let msg = "Can't use systf() on $rstb_unknown, which is no registered system task.";
+        assert_eq!(r, Err(Val::String(msg.to_string())));
+    }
+
+    #[test]
+    fn reregister_with_other_kind() {
+        register_systf("$rstb_twice", SysTfKind::IntFunc, |_| Val::Int(1)).unwrap();
+        register_systf("$rstb_twice", SysTfKind::IntFunc, |_| Val::Int(2)).unwrap();
+        assert_eq!(
+            register_systf("$rstb_twice", SysTfKind::RealFunc, |_| Val::Float(3.0)),
+            Err(RstbError::InvalidValue {
+                op: "register_systf",
+                object: "$rstb_twice".to_string(),
+                value: "RealFunc".to_string(),
+            })
+        );
+        let sim = MockSim::new("top");
+        let r = sim.run(|_| {
+            async {
+                assert_eq!(MockSim::call_systf("$rstb_twice", &[]), Val::Int(2));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
@@ -4,7 +4,7 @@ use crate::rstb_obj::RstbObjSafe;
 use crate::signal;
 use crate::RstbResult;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct RstbTests (
     Vec<RstbObjSafe<Test>>,
 );
@@ -16,7 +16,10 @@ impl RstbTests {
     pub fn len(&self) -> usize {
         self.0.len()
     }
-    pub fn iter(&self) -> core::slice::Iter<RstbObjSafe<Test>> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn iter(&self) -> core::slice::Iter<'_, RstbObjSafe<Test>> {
         self.0.iter()
     }
     pub fn push(&mut self, test: Test) {
@@ -31,11 +34,13 @@ pub struct Test {
     pub time_secs: f64,
     pub sim_time_ns: u64,
     pub result: Option<RstbResult>,
+    // not selected by +TESTCASE
+    pub skipped: bool,
 }
 
 impl Test {
     pub fn new(name: String, generator: fn(signal::SimObject) -> BoxFuture<'static, RstbResult>) -> Self {
-        Self { name, generator, time_secs: 0.0, sim_time_ns: 0, result: None }
+        Self { name, generator, time_secs: 0.0, sim_time_ns: 0, result: None, skipped: false }
     }
     pub fn set_result(&mut self, result: RstbResult) {
         self.result = Some(result);
@@ -43,3 +48,5 @@ impl Test {
 }
 
 pub(crate) static TESTS: OnceCell<RstbTests> = OnceCell::new();
+// names given with +TESTCASE that don't match a test, reported as failed
+pub(crate) static UNKNOWN_TESTS: OnceCell<Vec<String>> = OnceCell::new();
@@ -129,7 +129,7 @@ struct MonitorInner<T: PartialEq> {
 pub async fn clock(clk: SimObject, period: u32, unit: &str) -> RstbResult {
     let high_t = period / 2;
     let low_t = period - high_t;
-    if period % 2 != 0 {
+    if !period.is_multiple_of(2) {
         SIM_IF.log(&format!("Warning: Clock period {period}{unit} not dividable by 2. High time will be {high}{unit}; low time will be {low}{unit}.", period=period, unit=unit, high=high_t, low=low_t));
     }
     loop {
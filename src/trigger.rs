@@ -1,15 +1,22 @@
+// callback maps are `static mut`, simulator callbacks all run on the same thread
+#![allow(static_mut_refs)]
+
 use intmap::IntMap;
 use lazy_mut::lazy_mut;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll, Waker};
 
 use crate::executor;
+use crate::signal;
+use crate::systf;
 use crate::{
-    RstbResult,
+    RstbResult, SimResult,
     signal::SimObject,
-    sim_if::SIM_IF,
+    sim_if::{ObjectKind, SimIf, SIM_IF},
     value::Val,
 };
 
@@ -23,17 +30,83 @@ lazy_mut! {
     static mut TIMER_MAP: IntMap<CallbackHandles> = IntMap::new();
 }
 lazy_mut! {
-    static mut READ_ONLY: CallbackHandles = CallbackHandles { handle: None, callbacks: VecDeque::new() };
+    // key is absolute time of the time step
+    static mut START_OF_STEP_MAP: IntMap<CallbackHandles> = IntMap::new();
+}
+lazy_mut! {
+    static mut NEXT_TIME_STEP: CallbackHandles = CallbackHandles::new(*SIM_IF);
+}
+lazy_mut! {
+    static mut NBA: CallbackHandles = CallbackHandles::new(*SIM_IF);
+}
+lazy_mut! {
+    static mut READ_ONLY: CallbackHandles = CallbackHandles::new(*SIM_IF);
 }
 lazy_mut! {
-    static mut READ_WRITE: CallbackHandles = CallbackHandles { handle: None, callbacks: VecDeque::new() };
+    static mut READ_WRITE: CallbackHandles = CallbackHandles::new(*SIM_IF);
+}
+lazy_mut! {
+    // key is the name of the system task or DPI function, calls don't need simulator callbacks
+    static mut CALL_MAP: HashMap<String, VecDeque<TrigShared>> = HashMap::new();
+}
+// Arguments of the call currently waking its triggers
+static mut CALL_ARGS: Option<Val> = None;
+
+// Synchronization region of the simulator callback currently being executed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SyncRegion {
+    Other,
+    ReadWrite,
+    ReadOnly,
+}
+static mut SYNC_REGION: SyncRegion = SyncRegion::Other;
+
+pub(crate) fn sync_region() -> SyncRegion {
+    unsafe { SYNC_REGION }
+}
+
+// Returns the previous region, callbacks may be nested.
+fn enter_region(region: SyncRegion) -> SyncRegion {
+    unsafe { std::mem::replace(&mut SYNC_REGION, region) }
 }
 
 struct CallbackHandles {
     handle: Option<usize>,
+    // interface which registered the callback
+    sim_if: &'static (dyn SimIf + Sync),
     callbacks: VecDeque<TrigShared>,
 }
 
+impl CallbackHandles {
+    fn new(sim_if: &'static (dyn SimIf + Sync)) -> Self {
+        CallbackHandles {
+            handle: None,
+            sim_if,
+            callbacks: VecDeque::new(),
+        }
+    }
+    // Removes a trigger's entry, returns true if no other trigger waits on the callback.
+    fn remove(&mut self, fired: &Arc<AtomicBool>) -> bool {
+        self.callbacks.retain(|t| !Arc::ptr_eq(&t.fired, fired));
+        self.callbacks.is_empty()
+    }
+    // Called from Drop, so a failure is only logged
+    fn cancel(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = self.sim_if.cancel_callback(handle) {
+                self.sim_if.log(&format!("Couldn't cancel callback: {}", e));
+            }
+        }
+    }
+}
+
+// Removes a trigger's entry from a callback map
+fn remove_from_map(map: &mut IntMap<CallbackHandles>, key: u64, fired: &Arc<AtomicBool>) {
+    if map.get_mut(key).is_some_and(|cbh| cbh.remove(fired)) {
+        map.remove(key).unwrap().cancel();
+    }
+}
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum EdgeKind {
     Any,
@@ -42,52 +115,122 @@ pub enum EdgeKind {
 }
 
 pub(crate) fn cancel_all_triggers() {
+    // Wakers may hold the last reference to a task, whose triggers deregister themselves when
+    // it is dropped. They are dropped once all callbacks are gone.
+    let mut wakers: Vec<VecDeque<TrigShared>> = Vec::new();
     unsafe {
         // RO
-        READ_ONLY.callbacks = VecDeque::new();
-        if let Some(handle) = READ_ONLY.handle.take() {
-            SIM_IF.cancel_callback(handle).unwrap();
-        }
-        // RW
-        READ_WRITE.callbacks = VecDeque::new();
-        if let Some(handle) = READ_WRITE.handle.take() {
-            SIM_IF.cancel_callback(handle).unwrap();
+        wakers.push(std::mem::take(&mut READ_ONLY.callbacks));
+        READ_ONLY.cancel();
+        // RW, unless writes are still waiting for it
+        wakers.push(std::mem::take(&mut READ_WRITE.callbacks));
+        if !signal::has_queued_writes() {
+            READ_WRITE.cancel();
         }
         // Timers
-        for (_, cb) in TIMER_MAP.drain() {
-            // TaskCallbacks will be dropped with this context
-            SIM_IF.cancel_callback(cb.handle.unwrap()).unwrap();
+        for (_, mut cb) in TIMER_MAP.drain() {
+            wakers.push(std::mem::take(&mut cb.callbacks));
+            cb.cancel();
+        }
+        for (_, mut cb) in START_OF_STEP_MAP.drain() {
+            wakers.push(std::mem::take(&mut cb.callbacks));
+            cb.cancel();
+        }
+        // Time step and NBA
+        for cbh in [&mut *NEXT_TIME_STEP, &mut *NBA] {
+            wakers.push(std::mem::take(&mut cbh.callbacks));
+            cbh.cancel();
         }
         // Edges
-        for (_, cb) in EDGE_MAP.drain() {
-            // TaskCallbacks will be dropped with this context
-            SIM_IF.cancel_callback(cb.handle.unwrap()).unwrap();
+        for (_, mut cb) in EDGE_MAP.drain() {
+            wakers.push(std::mem::take(&mut cb.callbacks));
+            cb.cancel();
+        }
+        wakers.extend(CALL_MAP.drain().map(|(_, callbacks)| callbacks));
+    }
+}
+
+#[cfg(feature = "mock")]
+pub(crate) fn clear_all_triggers() {
+    // Forgets all triggers without cancelling their callbacks. Only to be used if the
+    // simulator's callbacks are discarded as well.
+    let mut wakers: Vec<VecDeque<TrigShared>> = Vec::new();
+    unsafe {
+        for cbh in [
+            &mut *READ_ONLY,
+            &mut *READ_WRITE,
+            &mut *NEXT_TIME_STEP,
+            &mut *NBA,
+        ] {
+            cbh.handle = None;
+            wakers.push(std::mem::take(&mut cbh.callbacks));
+        }
+        for map in [&mut *TIMER_MAP, &mut *START_OF_STEP_MAP, &mut *EDGE_MAP] {
+            wakers.extend(map.drain().map(|(_, cb)| cb.callbacks));
         }
+        wakers.extend(CALL_MAP.drain().map(|(_, callbacks)| callbacks));
+        SYNC_REGION = SyncRegion::Other;
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct TrigShared {
     waker: Waker,
+    // shared with the awaiting Trigger, identifies its entry
+    fired: Arc<AtomicBool>,
     // If trigger is an edge, the react method needs to know if it is a rising or falling edge
     // so an existing callback does not have to be rescheduled.
     edge_kind: EdgeKind,
+    // Emulated bit or part select and its last seen value. The callback is registered on the
+    // parent vector, so the select's own edges must be filtered in the react method.
+    select: Option<(SimObject, String)>,
+}
+
+impl TrigShared {
+    // Edge seen by this trigger, None if its select did not change
+    fn edge(&mut self, edge: EdgeKind) -> Option<EdgeKind> {
+        match self.select.as_mut() {
+            None => Some(edge),
+            Some((signal, last)) => {
+                let value = signal.bin();
+                if value == *last {
+                    return None;
+                }
+                *last = value;
+                match (signal.size(), last.as_str()) {
+                    (1, "1" | "H" | "h") => Some(EdgeKind::Rising),
+                    (1, _) => Some(EdgeKind::Falling),
+                    _ => Some(EdgeKind::Any),
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
 pub enum TrigKind {
-    Edge(usize, EdgeKind),
+    Edge(SimObject, EdgeKind),
     Timer(u64),
+    // absolute time in simulation steps
+    At(u64),
     ReadWrite,
     ReadOnly,
+    NextTimeStep,
+    // relative time in simulation steps
+    StartOfTimeStep(u64),
+    Nba,
+    // call of a system task or DPI function by name
+    Call(String),
 }
 
-#[derive(Clone)]
 pub struct Trigger {
     kind: TrigKind,
-    awaited: bool,
+    // None until awaited, set once the callback woke the trigger
+    fired: Option<Arc<AtomicBool>>,
     // high exec prio currently only implemented for ReadOnly
     high_exec_prio: bool,
+    // key of timer and start of time step triggers in their callback map, set when awaited
+    abs_time: Option<u64>,
 }
 
 impl Trigger {
@@ -95,15 +238,32 @@ impl Trigger {
         // SIM_IF.log(&format!("TIMER: {}{}", time, unit));
         Trigger {
             kind: TrigKind::Timer(SIM_IF.get_sim_steps(time as f64, unit)),
-            awaited: false,
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
         }
     }
     pub fn timer_steps(steps: u64) -> Self {
         Trigger {
             kind: TrigKind::Timer(steps),
-            awaited: false,
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
+        }
+    }
+    /// Absolute simulation time `time`. A time in the past fails the running test, when the
+    /// trigger is created or when it is awaited.
+    pub fn at(time: u64, unit: &str) -> Self {
+        let abs_time = SIM_IF.get_sim_steps(time as f64, unit);
+        let now = SIM_IF.get_sim_time_steps();
+        if abs_time < now {
+            fail_past_time(abs_time, now);
+        }
+        Trigger {
+            kind: TrigKind::At(abs_time),
+            fired: None,
+            high_exec_prio: false,
+            abs_time: None,
         }
     }
     pub async fn timer_ro(time: u64, unit: &str) -> RstbResult {
@@ -118,46 +278,214 @@ impl Trigger {
     }
     pub fn edge(signal: SimObject) -> Self {
         Trigger {
-            kind: TrigKind::Edge(signal.handle(), EdgeKind::Any),
-            awaited: false,
+            kind: TrigKind::Edge(signal, EdgeKind::Any),
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
         }
     }
     pub fn rising_edge(signal: SimObject) -> Self {
+        check_bits(&signal, "rising_edge");
         Trigger {
-            kind: TrigKind::Edge(signal.handle(), EdgeKind::Rising),
-            awaited: false,
+            kind: TrigKind::Edge(signal, EdgeKind::Rising),
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
         }
     }
     pub fn falling_edge(signal: SimObject) -> Self {
+        check_bits(&signal, "falling_edge");
         Trigger {
-            kind: TrigKind::Edge(signal.handle(), EdgeKind::Falling),
-            awaited: false,
+            kind: TrigKind::Edge(signal, EdgeKind::Falling),
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
         }
     }
     pub fn read_write() -> Self {
         Trigger {
             kind: TrigKind::ReadWrite,
-            awaited: false,
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
         }
     }
     pub fn read_only() -> Self {
         Trigger {
             kind: TrigKind::ReadOnly,
-            awaited: false,
+            fired: None,
+            high_exec_prio: false,
+            abs_time: None,
+        }
+    }
+    /// Start of the next time step in which any event is scheduled. Never resolves if the
+    /// simulation is idle.
+    pub fn next_time_step() -> Self {
+        Trigger {
+            kind: TrigKind::NextTimeStep,
+            fired: None,
+            high_exec_prio: false,
+            abs_time: None,
+        }
+    }
+    /// Start of the time step `time` from now, before any of its events are executed. Signals
+    /// still hold the values of the previous time step, like sampled values in a clocking block.
+    pub fn start_of_time_step(time: u64, unit: &str) -> Self {
+        Trigger {
+            kind: TrigKind::StartOfTimeStep(SIM_IF.get_sim_steps(time as f64, unit)),
+            fired: None,
             high_exec_prio: false,
+            abs_time: None,
+        }
+    }
+    /// Before the nonblocking assignments of the current time step are applied. Through VHPI,
+    /// after all processes of the current delta cycle have run. Requires cbNBASynch support
+    /// from VPI simulators.
+    pub fn nba() -> Self {
+        Trigger {
+            kind: TrigKind::Nba,
+            fired: None,
+            high_exec_prio: false,
+            abs_time: None,
+        }
+    }
+    /// Resolves to the index and value of the first of `triggers` to fire. The others are
+    /// removed, their callbacks cancelled unless other tasks wait on them.
+    pub fn first_of(triggers: impl IntoIterator<Item = Trigger>) -> FirstOf {
+        let triggers: Vec<Trigger> = triggers.into_iter().collect();
+        if triggers.is_empty() {
+            panic!("Can't use first_of() without triggers.");
+        }
+        FirstOf { triggers }
+    }
+    /// Resolves once all of `triggers` fired, to their values in the given order.
+    pub fn all_of(triggers: impl IntoIterator<Item = Trigger>) -> AllOf {
+        AllOf {
+            triggers: triggers.into_iter().map(|t| (t, None)).collect(),
+        }
+    }
+    /// Resolves to `Some` value of the trigger if it fires within `time`, `None` otherwise.
+    pub async fn with_timeout(self, time: u64, unit: &str) -> Option<Val> {
+        match Trigger::first_of([self, Trigger::timer(time, unit)]).await {
+            (0, val) => Some(val),
+            _ => None,
+        }
+    }
+    // Removes the entry of an awaited trigger which did not fire, and cancels its callback if
+    // no other trigger waits on it.
+    pub(crate) fn cancel(&self) {
+        let fired = match &self.fired {
+            Some(fired) if !fired.load(Ordering::Relaxed) => fired,
+            _ => return,
+        };
+        unsafe {
+            match &self.kind {
+                TrigKind::Edge(signal, _) => {
+                    remove_from_map(&mut EDGE_MAP, signal.handle() as u64, fired)
+                }
+                // no key if an At trigger was in the past and never registered
+                TrigKind::Timer(_) | TrigKind::At(_) => {
+                    if let Some(abs_time) = self.abs_time {
+                        remove_from_map(&mut TIMER_MAP, abs_time, fired)
+                    }
+                }
+                TrigKind::StartOfTimeStep(_) => {
+                    if let Some(abs_time) = self.abs_time {
+                        remove_from_map(&mut START_OF_STEP_MAP, abs_time, fired)
+                    }
+                }
+                TrigKind::ReadWrite => {
+                    // writes may still wait for the callback
+                    if READ_WRITE.remove(fired) && !signal::has_queued_writes() {
+                        READ_WRITE.cancel();
+                    }
+                }
+                TrigKind::ReadOnly => {
+                    if READ_ONLY.remove(fired) {
+                        READ_ONLY.cancel();
+                    }
+                }
+                TrigKind::NextTimeStep => {
+                    if NEXT_TIME_STEP.remove(fired) {
+                        NEXT_TIME_STEP.cancel();
+                    }
+                }
+                TrigKind::Nba => {
+                    if NBA.remove(fired) {
+                        NBA.cancel();
+                    }
+                }
+                TrigKind::Call(name) => {
+                    if let Some(callbacks) = CALL_MAP.get_mut(name) {
+                        callbacks.retain(|t| !Arc::ptr_eq(&t.fired, fired));
+                        if callbacks.is_empty() {
+                            CALL_MAP.remove(name);
+                        }
+                    }
+                }
+            }
         }
     }
     pub(crate) fn read_only_prio() -> Self {
         Trigger {
             kind: TrigKind::ReadOnly,
-            awaited: false,
+            fired: None,
             high_exec_prio: true,
+            abs_time: None,
+        }
+    }
+    /// Next call of the system task or function `name` from HDL code. Resolves to the call's
+    /// arguments as `Val::Vec`.
+    pub fn systf(name: &str) -> Self {
+        // the trigger never fires, failing the test tears it down
+        if !systf::is_registered(name) {
+            fail_in_callback(&format!(
+                "Can't use systf() on {}, which is no registered system task.",
+                name
+            ));
+        }
+        Trigger {
+            kind: TrigKind::Call(name.to_string()),
+            fired: None,
+            high_exec_prio: false,
+            abs_time: None,
         }
     }
+    /// Next call of the DPI handler `name` from SystemVerilog. Resolves to the call's arguments
+    /// as `Val::Vec`.
+    #[cfg(feature = "dpi")]
+    pub fn dpi(name: &str) -> Self {
+        if !crate::dpi::is_registered(name) {
+            panic!(
+                "Can't use dpi() on {}, which is no registered DPI handler.",
+                name
+            );
+        }
+        Trigger {
+            kind: TrigKind::Call(name.to_string()),
+            fired: None,
+            high_exec_prio: false,
+            abs_time: None,
+        }
+    }
+}
+
+// Panicking in poll() or a callback would abort the simulator inside its callback
+pub(crate) fn fail_in_callback(msg: &str) {
+    SIM_IF.log(msg);
+    crate::fail_test(msg);
+}
+
+fn fail_past_time(abs_time: u64, now: u64) {
+    fail_in_callback(&format!(
+        "Can't use at() on time {} steps, simulation time is already {} steps.",
+        abs_time, now
+    ));
+}
+
+// Handle of a newly registered callback, a failed registration fails the test
+fn registered(res: SimResult<usize>) -> Option<usize> {
+    res.map_err(|e| fail_in_callback(&e.to_string())).ok()
 }
 
 impl Future for Trigger {
@@ -165,76 +493,139 @@ impl Future for Trigger {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         // vpi::log("Polling trigger");
-        // Trigger must only be awaited once, so the second time it is polled it must be because
-        // the waker signaled its completion.
-        if self.awaited {
+        // Trigger must only be awaited once. Combined triggers are polled whenever one of them
+        // fires, so the others stay pending until their own callback fired.
+        if let Some(fired) = &self.fired {
+            if !fired.load(Ordering::Relaxed) {
+                return Poll::Pending;
+            }
             // vpi::log("Trigger done!");
-            Poll::Ready(Val::None)
+            match self.kind {
+                TrigKind::Call(_) => {
+                    Poll::Ready(unsafe { CALL_ARGS.clone() }.unwrap_or(Val::None))
+                }
+                _ => Poll::Ready(Val::None),
+            }
         } else {
-            self.awaited = true;
+            let fired = Arc::new(AtomicBool::new(false));
+            self.fired = Some(fired.clone());
             // vpi::log("Initilaize new Trigger!");
             // initialize new Trigger
             let mut shared = TrigShared {
                 waker: cx.waker().clone(),
+                fired,
                 edge_kind: EdgeKind::Any,
+                select: None,
             };
 
-            match self.kind {
-                TrigKind::ReadWrite => unsafe {
-                    READ_WRITE.callbacks.push_back(shared);
-                    if READ_WRITE.handle.is_none() {
-                        let cb_hdl = SIM_IF.register_callback_rw().unwrap();
-                        READ_WRITE.handle.replace(cb_hdl);
+            match &self.kind {
+                // Failed registrations fail the test, which tears it down. The trigger never fires.
+                TrigKind::ReadWrite => {
+                    if !request_read_write() {
+                        return Poll::Pending;
                     }
-                },
+                    unsafe { READ_WRITE.callbacks.push_back(shared) };
+                }
                 TrigKind::ReadOnly => unsafe {
+                    if READ_ONLY.handle.is_none() {
+                        match registered(SIM_IF.register_callback_ro()) {
+                            Some(cb_hdl) => READ_ONLY.handle = Some(cb_hdl),
+                            None => return Poll::Pending,
+                        }
+                    }
                     match self.high_exec_prio {
                         false => READ_ONLY.callbacks.push_back(shared),
                         true => READ_ONLY.callbacks.push_front(shared),
                     }
-                    if READ_ONLY.handle.is_none() {
-                        let cb_hdl = SIM_IF.register_callback_ro().unwrap();
-                        READ_ONLY.handle.replace(cb_hdl);
+                },
+                TrigKind::NextTimeStep => unsafe {
+                    if NEXT_TIME_STEP.handle.is_none() {
+                        match registered(SIM_IF.register_callback_next_time_step()) {
+                            Some(cb_hdl) => NEXT_TIME_STEP.handle = Some(cb_hdl),
+                            None => return Poll::Pending,
+                        }
                     }
+                    NEXT_TIME_STEP.callbacks.push_back(shared);
                 },
-                TrigKind::Timer(t) => {
-                    // Add current time to key since since simulator will send back absolute time, not delta
+                TrigKind::Nba => unsafe {
+                    if NBA.handle.is_none() {
+                        match registered(SIM_IF.register_callback_nba()) {
+                            Some(cb_hdl) => NBA.handle = Some(cb_hdl),
+                            None => return Poll::Pending,
+                        }
+                    }
+                    NBA.callbacks.push_back(shared);
+                },
+                &TrigKind::StartOfTimeStep(t) => {
                     let abs_time = t + SIM_IF.get_sim_time_steps();
+                    self.abs_time = Some(abs_time);
+                    if let Some(callbacks) = unsafe { START_OF_STEP_MAP.get_mut(abs_time) } {
+                        callbacks.callbacks.push_back(shared);
+                    } else {
+                        let mut callback = CallbackHandles::new(*SIM_IF);
+                        callback.handle =
+                            registered(SIM_IF.register_callback_start_of_time_step(t));
+                        if callback.handle.is_none() {
+                            return Poll::Pending;
+                        }
+                        callback.callbacks.push_back(shared);
+                        unsafe { START_OF_STEP_MAP.insert(abs_time, callback) };
+                    }
+                }
+                TrigKind::Timer(_) | TrigKind::At(_) => {
+                    // Add current time to key since since simulator will send back absolute time, not delta
+                    let now = SIM_IF.get_sim_time_steps();
+                    let (t, abs_time) = match self.kind {
+                        // never fires, the test is torn down
+                        TrigKind::At(abs_time) if abs_time < now => {
+                            fail_past_time(abs_time, now);
+                            return Poll::Pending;
+                        }
+                        TrigKind::At(abs_time) => (abs_time - now, abs_time),
+                        TrigKind::Timer(t) => (t, t + now),
+                        _ => unreachable!(),
+                    };
+                    self.abs_time = Some(abs_time);
                     if let Some(callbacks) = unsafe { TIMER_MAP.get_mut(abs_time) } {
                         callbacks.callbacks.push_back(shared);
                     } else {
                         // SIM_IF.log("Registering time");
-                        let handle = SIM_IF.register_callback_time(t).unwrap();
-                        let mut vec = VecDeque::new();
-                        vec.push_back(shared);
-                        let callback = CallbackHandles {
-                            handle: Some(handle),
-                            callbacks: vec,
-                        };
+                        let mut callback = CallbackHandles::new(*SIM_IF);
+                        callback.handle = registered(SIM_IF.register_callback_time(t));
+                        if callback.handle.is_none() {
+                            return Poll::Pending;
+                        }
+                        callback.callbacks.push_back(shared);
                         unsafe { TIMER_MAP.insert(abs_time, callback) };
                     }
                 }
-                TrigKind::Edge(sig_hdl, edge_kind) => {
+                &TrigKind::Edge(signal, edge_kind) => {
                     // SIM_IF.log("Edge trigger");
+                    let sig_hdl = signal.handle();
                     shared.edge_kind = edge_kind;
+                    if signal.is_emulated_select() {
+                        shared.select = Some((signal, signal.bin()));
+                    }
                     if let Some(callbacks) = unsafe { EDGE_MAP.get_mut(sig_hdl as u64) } {
                         // vpi::log("Callback already exists. Appending.");
                         callbacks.callbacks.push_back(shared);
                     } else {
                         // vpi::log("Registering callback.");
                         // SIM_IF.log("Registering edge");
-                        let handle = SIM_IF
-                            .register_callback_edge(sig_hdl)
-                            .unwrap();
-                        let mut vec = VecDeque::new();
-                        vec.push_back(shared);
-                        let callback = CallbackHandles {
-                            handle: Some(handle),
-                            callbacks: vec,
-                        };
+                        // the signal's own interface must register the callback
+                        let mut callback = CallbackHandles::new(signal.sim_if());
+                        callback.handle =
+                            registered(signal.sim_if().register_callback_edge(sig_hdl));
+                        if callback.handle.is_none() {
+                            return Poll::Pending;
+                        }
+                        callback.callbacks.push_back(shared);
                         unsafe { EDGE_MAP.insert(sig_hdl as u64, callback) };
                     }
                 }
+                TrigKind::Call(name) => unsafe {
+                    CALL_MAP.entry(name.clone()).or_default().push_back(shared);
+                },
             }
             // vpi::log("Done: Initilaize Trigger!");
             Poll::Pending
@@ -242,28 +633,122 @@ impl Future for Trigger {
     }
 }
 
+// A clone is a new trigger, which is not awaited yet.
+impl Clone for Trigger {
+    fn clone(&self) -> Self {
+        Trigger {
+            kind: self.kind.clone(),
+            fired: None,
+            high_exec_prio: self.high_exec_prio,
+            abs_time: None,
+        }
+    }
+}
+
+// Triggers dropped before they fired, e.g. in cancelled tasks, deregister themselves.
+impl Drop for Trigger {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Future returned by `Trigger::first_of()`
+pub struct FirstOf {
+    triggers: Vec<Trigger>,
+}
+
+impl Future for FirstOf {
+    type Output = (usize, Val);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        for i in 0..self.triggers.len() {
+            if let Poll::Ready(val) = Pin::new(&mut self.triggers[i]).poll(cx) {
+                for trigger in self.triggers.iter() {
+                    trigger.cancel();
+                }
+                return Poll::Ready((i, val));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Future returned by `Trigger::all_of()`
+pub struct AllOf {
+    triggers: Vec<(Trigger, Option<Val>)>,
+}
+
+impl Future for AllOf {
+    type Output = Vec<Val>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        for (trigger, val) in self.triggers.iter_mut().filter(|(_, val)| val.is_none()) {
+            if let Poll::Ready(v) = Pin::new(trigger).poll(cx) {
+                val.replace(v);
+            }
+        }
+        if self.triggers.iter().all(|(_, val)| val.is_some()) {
+            Poll::Ready(
+                self.triggers
+                    .iter_mut()
+                    .map(|(_, val)| val.take().unwrap())
+                    .collect(),
+            )
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+// Real and string valued signals only have value changes, not rising or falling edges.
+fn check_bits(signal: &SimObject, trigger: &str) {
+    if matches!(signal.kind(), ObjectKind::Real | ObjectKind::String) {
+        panic!(
+            "Can't use {}() on {:?} signal {}, use edge() instead.",
+            trigger,
+            signal.kind(),
+            signal.name()
+        );
+    }
+}
 
 #[inline]
 fn wake(vec_wake: VecDeque<TrigShared>) {
     for shared in vec_wake {
+        shared.fired.store(true, Ordering::Relaxed);
         shared.waker.wake();
     }
     // execute woken tasks
     executor::run_once();
 }
 
+// Registers the ReadWrite callback, if it isn't already. Returns false if that failed, which
+// fails the test.
+pub(crate) fn request_read_write() -> bool {
+    unsafe {
+        if READ_WRITE.handle.is_none() {
+            READ_WRITE.handle = registered(SIM_IF.register_callback_rw());
+        }
+        READ_WRITE.handle.is_some()
+    }
+}
+
 #[inline]
 pub(crate) fn react_rw() {
     let vec_wake;
     unsafe {
         READ_WRITE.handle = None; // remove handle, since CB is now done
-        if !READ_WRITE.callbacks.is_empty() {
-            vec_wake = std::mem::take(&mut READ_WRITE.callbacks);
-        } else {
-            panic!("Did not expect ReadOnly callback");
+        if READ_WRITE.callbacks.is_empty() && !signal::has_queued_writes() {
+            SIM_IF.log("Did not expect ReadWrite callback");
+            return;
         }
+        vec_wake = std::mem::take(&mut READ_WRITE.callbacks);
     }
+    let prev = enter_region(SyncRegion::ReadWrite);
+    // writes are applied before any task continues
+    signal::apply_queued_writes();
     wake(vec_wake);
+    enter_region(prev);
 }
 
 #[inline]
@@ -274,42 +759,80 @@ pub(crate) fn react_ro() {
         if !READ_ONLY.callbacks.is_empty() {
             vec_wake = std::mem::take(&mut READ_ONLY.callbacks);
         } else {
-            panic!("Did not expect ReadOnly callback");
+            SIM_IF.log("Did not expect ReadOnly callback");
+            return;
         }
     }
+    let prev = enter_region(SyncRegion::ReadOnly);
     wake(vec_wake);
+    enter_region(prev);
 }
 
 #[inline]
 pub(crate) fn react_time(t: u64) {
     // SIM_IF.log("Reacting time");
-    let cbh = unsafe { TIMER_MAP.remove(t).expect("Did not expect Timer callback at given time") };
-    wake(cbh.callbacks);
+    match unsafe { TIMER_MAP.remove(t) } {
+        Some(cbh) => wake(cbh.callbacks),
+        None => SIM_IF.log("Did not expect Timer callback at given time"),
+    }
+}
+
+#[inline]
+pub(crate) fn react_next_time_step() {
+    let vec_wake = unsafe {
+        NEXT_TIME_STEP.handle = None;
+        std::mem::take(&mut NEXT_TIME_STEP.callbacks)
+    };
+    wake(vec_wake);
+}
+
+#[inline]
+pub(crate) fn react_start_of_time_step(t: u64) {
+    match unsafe { START_OF_STEP_MAP.remove(t) } {
+        Some(cbh) => wake(cbh.callbacks),
+        None => SIM_IF.log("Did not expect start of time step callback at given time"),
+    }
+}
+
+#[inline]
+pub(crate) fn react_nba() {
+    let vec_wake = unsafe {
+        NBA.handle = None;
+        std::mem::take(&mut NBA.callbacks)
+    };
+    wake(vec_wake);
 }
 
 #[inline]
 pub(crate) fn react_edge(sig_hdl: usize, edge: EdgeKind) {
-    let mut cbh = unsafe { EDGE_MAP.remove(sig_hdl as u64) }.unwrap();
+    let mut cbh = match unsafe { EDGE_MAP.remove(sig_hdl as u64) } {
+        Some(cbh) => cbh,
+        None => {
+            SIM_IF.log("Did not expect edge callback for given signal");
+            return;
+        }
+    };
     let mut vec_wake;
     match edge {
-        EdgeKind::Any => {
+        EdgeKind::Any if cbh.callbacks.iter().all(|t| t.select.is_none()) => {
             vec_wake = std::mem::take(&mut cbh.callbacks);
             // SIM_IF.log("Any: cancelling callback");
-            SIM_IF.cancel_callback(cbh.handle.unwrap()).unwrap();
+            cbh.cancel();
         }
         _ => {
             vec_wake = VecDeque::with_capacity(cbh.callbacks.len());
             let mut vec_resched: VecDeque<TrigShared> = VecDeque::with_capacity(cbh.callbacks.len());
             // dbg!(&cbh.callbacks);
-            for trig in cbh.callbacks.drain(..) {
-                if trig.edge_kind == EdgeKind::Any || trig.edge_kind == edge {
-                    vec_wake.push_back(trig);
-                } else {
-                    vec_resched.push_back(trig);
+            for mut trig in cbh.callbacks.drain(..) {
+                match trig.edge(edge) {
+                    Some(e) if trig.edge_kind == EdgeKind::Any || trig.edge_kind == e => {
+                        vec_wake.push_back(trig)
+                    }
+                    _ => vec_resched.push_back(trig),
                 }
             }
             if vec_resched.is_empty() {
-                SIM_IF.cancel_callback(cbh.handle.unwrap()).unwrap();
+                cbh.cancel();
                 // SIM_IF.log("Cancelling edge callback");
             } else {
                 // std::mem::replace(&mut cbh.callbacks, vec_resched);
@@ -322,3 +845,312 @@ pub(crate) fn react_edge(sig_hdl: usize, edge: EdgeKind) {
 
     wake(vec_wake);
 }
+
+#[inline]
+pub(crate) fn react_call(name: &str, args: Vec<Val>) {
+    let vec_wake = match unsafe { CALL_MAP.remove(name) } {
+        Some(callbacks) => callbacks,
+        None => return,
+    };
+    unsafe { CALL_ARGS = Some(Val::Vec(args)) };
+    wake(vec_wake);
+    unsafe { CALL_ARGS = None };
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use crate::executor::{JoinHandle, Task};
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn now_ns() -> u64 {
+        SIM_IF.get_sim_time("ns")
+    }
+
+    #[test]
+    fn at_absolute_time() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                Trigger::timer(3, "ns").await;
+                Trigger::at(10, "ns").await;
+                assert_eq!(now_ns(), 10);
+                // created before, awaited later
+                let at = Trigger::at(25, "ns");
+                Trigger::timer(5, "ns").await;
+                at.await;
+                assert_eq!(now_ns(), 25);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn at_past_time_fails_test() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                Trigger::timer(20, "ns").await;
+                drop(Trigger::at(10, "ns"));
+                Trigger::timer(1, "ns").await;
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        let msg = "Can't use at() on time 10000 steps, simulation time is already 20000 steps.";
+        assert_eq!(r, Err(Val::String(msg.to_string())));
+    }
+
+    #[test]
+    fn failed_registration_fails_test() {
+        let r = MockSim::new("top").run(|dut| {
+            async move {
+                // scopes have no value to put a value change callback on
+                dut.edge().await;
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        let msg = match r {
+            Err(Val::String(msg)) => msg,
+            _ => panic!("expected a failed test, got {:?}", r),
+        };
+        assert!(msg.contains("register_callback_edge"), "{}", msg);
+        assert!(no_waiters());
+    }
+
+    #[test]
+    fn at_awaited_too_late_fails_test() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                let at = Trigger::at(30, "ns");
+                Trigger::timer(40, "ns").await;
+                at.await;
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        let msg = "Can't use at() on time 30000 steps, simulation time is already 40000 steps.";
+        assert_eq!(r, Err(Val::String(msg.to_string())));
+    }
+
+    fn done() -> MockSim {
+        MockSim::new("top").signal("done", 1)
+    }
+
+    #[allow(static_mut_refs)]
+    fn no_waiters() -> bool {
+        let maps_empty = unsafe { super::EDGE_MAP.is_empty() && super::TIMER_MAP.is_empty() };
+        maps_empty && crate::mock::pending_callbacks() == 0
+    }
+
+    #[test]
+    fn first_of_removes_losers() {
+        let r = done().run(|dut| {
+            async move {
+                let done = dut.c("done");
+                done.set(0);
+                done.set_after(1, 5, "ns");
+                let first = Trigger::first_of([done.rising_edge(), Trigger::timer(10, "ns")]);
+                let (i, _) = first.await;
+                assert_eq!((i, now_ns()), (0, 5));
+                Trigger::read_only().await;
+                assert!(no_waiters());
+                let (i, _) = Trigger::first_of([done.edge(), Trigger::timer(10, "ns")]).await;
+                assert_eq!((i, now_ns()), (1, 15));
+                Trigger::read_only().await;
+                assert!(no_waiters());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn all_of_waits_for_every_trigger() {
+        let r = done().run(|dut| {
+            async move {
+                let done = dut.c("done");
+                done.set(0);
+                done.set_after(1, 5, "ns");
+                let vals = Trigger::all_of([Trigger::timer(10, "ns"), done.rising_edge()]).await;
+                assert_eq!((vals.len(), now_ns()), (2, 10));
+                assert!(Trigger::all_of([]).await.is_empty());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn with_timeout() {
+        let r = done().run(|dut| {
+            async move {
+                let done = dut.c("done");
+                done.set(0);
+                assert_eq!(done.rising_edge().with_timeout(10, "ns").await, None);
+                assert_eq!(now_ns(), 10);
+                Trigger::read_only().await;
+                assert!(no_waiters());
+                done.set_after(1, 3, "ns");
+                let val = done.rising_edge().with_timeout(10, "ns").await;
+                assert_eq!((val, now_ns()), (Some(Val::None), 13));
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn cancelled_timers_removed_by_key() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                let a = Task::spawn(async {
+                    Trigger::timer(10, "ns").await;
+                    Ok(Val::None)
+                });
+                let b = Task::spawn(async {
+                    Trigger::at(10, "ns").await;
+                    Ok(Val::None)
+                });
+                Trigger::timer(1, "ns").await;
+                assert_eq!(crate::mock::pending_callbacks(), 1);
+                a.cancel();
+                // the shared time step keeps waiting for the other task
+                assert_eq!(crate::mock::pending_callbacks(), 1);
+                b.cancel();
+                assert!(no_waiters());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    type Log = Arc<Mutex<Vec<(&'static str, u64)>>>;
+
+    // Spawns a task which logs `name` and the time once `trigger` fired
+    fn log_when(log: &Log, name: &'static str, trigger: Trigger) -> JoinHandle {
+        let log = log.clone();
+        Task::spawn(async move {
+            trigger.await;
+            log.lock().unwrap().push((name, now_ns()));
+            Ok(Val::None)
+        })
+    }
+
+    #[test]
+    fn time_step_triggers_in_order() {
+        let r = done().run(|dut| {
+            async move {
+                let done = dut.c("done");
+                done.set(0);
+                Trigger::timer(1, "ns").await;
+                let log: Log = Arc::new(Mutex::new(Vec::new()));
+                done.set_after(1, 5, "ns");
+                log_when(&log, "timer", Trigger::timer(5, "ns"));
+                log_when(&log, "start", Trigger::start_of_time_step(5, "ns"));
+                log_when(&log, "next", Trigger::next_time_step());
+                // signals still hold the values of the previous time step
+                let sampled = Task::spawn(async move {
+                    Trigger::start_of_time_step(5, "ns").await;
+                    Ok(Val::Int(done.u32()))
+                });
+                Trigger::timer(5, "ns").await;
+                Trigger::read_only().await;
+                let log = log.lock().unwrap().clone();
+                assert_eq!(log, [("next", 6), ("start", 6), ("timer", 6)]);
+                assert_eq!(sampled.await, Ok(Val::Int(0)));
+                assert_eq!(done.u32(), 1);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn nba_before_read_write() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                Trigger::timer(2, "ns").await;
+                let log: Log = Arc::new(Mutex::new(Vec::new()));
+                log_when(&log, "rw", Trigger::read_write());
+                log_when(&log, "nba", Trigger::nba());
+                Trigger::read_only().await;
+                let log = log.lock().unwrap().clone();
+                // within the current time step
+                assert_eq!(log, [("nba", 2), ("rw", 2)]);
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    #[test]
+    fn time_step_triggers_cancelled() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                Trigger::timer(1, "ns").await;
+                let triggers = [
+                    Trigger::next_time_step(),
+                    Trigger::start_of_time_step(10, "ns"),
+                    Trigger::nba(),
+                ];
+                for mut trigger in triggers {
+                    assert!(futures::poll!(&mut trigger).is_pending());
+                    assert_eq!(crate::mock::pending_callbacks(), 1);
+                    drop(trigger);
+                    assert!(no_waiters());
+                }
+                let first = Trigger::start_of_time_step(20, "ns").with_timeout(10, "ns").await;
+                assert_eq!((first, now_ns()), (None, 11));
+                Trigger::read_only().await;
+                assert!(no_waiters());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn self_cancelled_task_drops_triggers() {
+        let r = MockSim::new("top").run(|_| {
+            async {
+                let slot = Arc::new(Mutex::new(None::<Arc<Task>>));
+                let dropped = Arc::new(AtomicBool::new(false));
+                let (task, flag) = (slot.clone(), DropFlag(dropped.clone()));
+                let handle = Task::spawn(async move {
+                    let _flag = flag;
+                    let mut timer = Trigger::timer(100, "ns");
+                    assert!(futures::poll!(&mut timer).is_pending());
+                    task.lock().unwrap().take().unwrap().cancel();
+                    timer.await;
+                    Ok(Val::None)
+                });
+                *slot.lock().unwrap() = handle.get_task();
+                assert_eq!(handle.await, Ok(Val::None));
+                assert!(dropped.load(Ordering::SeqCst));
+                Trigger::read_only().await;
+                assert!(no_waiters());
+                Ok(Val::None)
+            }
+            .boxed()
+        });
+        assert!(r.is_ok(), "{:?}", r);
+    }
+}
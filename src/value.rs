@@ -1,11 +1,14 @@
 
 
+use crate::logic::LogicVec;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Val {
     Int(u32),
     Signed(i32),
-    Float(f32),
+    Float(f64),
     BitStr(String),
+    Logic(LogicVec),
     Vec(Vec<Val>),
     String(String),
     None,
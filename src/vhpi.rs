@@ -1,58 +1,275 @@
 use std::ffi::CStr;
 
-use crate::sim_if::SimCallback;
+use crate::sim_if::{
+    get_interface, time_stamp, Interface, ObjectKind, PortDirection, SimControl, SimIf, WriteMode,
+    SIM_IF,
+};
 use crate::trigger;
 use crate::trigger::EdgeKind;
-use crate::{sim_if, sim_if::SIM_IF, sim_if::ObjectKind, vhpi_user, RstbErr, RstbResult};
+use crate::{vhpi_user, RstbError, SimResult};
 
-pub struct Vhpi {
-    // precision: i32,
+// Enumeration positions of std_logic values, in the order of the type declaration
+const STD_LOGIC_CHARS: [char; 9] = ['U', 'X', '0', '1', 'Z', 'W', 'L', 'H', '-'];
+
+pub(crate) struct Vhpi {
+    precision: i8,
+}
+
+// How values of an object are represented in VHPI
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum ValueClass {
+    Logic,
+    LogicVec,
+    Enum,
+    Int,
+    Real,
+    // array of CHARACTER
+    Str,
+    Array,
+    Other,
+}
+
+impl Vhpi {
+    pub fn new() -> Self {
+        Vhpi {
+            precision: get_time_precision(),
+        }
+    }
+    #[inline]
+    unsafe fn _register_callback(
+        &self,
+        reason: u32,
+        time: Option<vhpi_user::vhpiTimeT>,
+        obj: vhpi_user::vhpiHandleT,
+        cb_fun: unsafe extern "C" fn(*const vhpi_user::vhpiCbDataT),
+    ) -> SimResult<usize> {
+        let mut time = time;
+        let mut cb_data = vhpi_user::vhpiCbDataT {
+            reason: reason as i32,
+            cb_rtn: Some(cb_fun),
+            obj,
+            time: match time.as_mut() {
+                Some(t) => t,
+                None => std::ptr::null_mut(),
+            },
+            ..Default::default()
+        };
+        let hdl = vhpi_user::vhpi_register_cb(&mut cb_data, vhpi_user::vhpiReturnCb as i32);
+        check_null(hdl)
+            .map(|h| h as usize)
+            .ok_or_else(|| error("register_callback", obj as usize))
+    }
+    fn _put_value(
+        &self,
+        op: &'static str,
+        obj: usize,
+        val: &mut vhpi_user::vhpiValueT,
+        mode: WriteMode,
+    ) -> SimResult<()> {
+        // VHPI has no delay modes, delayed writes are scheduled by rstb
+        let mode = match mode {
+            WriteMode::Force => vhpi_user::vhpiPutValueModeT_vhpiForcePropagate,
+            WriteMode::NoDelay => vhpi_user::vhpiPutValueModeT_vhpiDeposit,
+            _ => vhpi_user::vhpiPutValueModeT_vhpiDepositPropagate,
+        };
+        match unsafe { vhpi_user::vhpi_put_value(obj as vhpi_user::vhpiHandleT, val, mode) } {
+            0 => Ok(()),
+            _ => Err(error(op, obj)),
+        }
+    }
+    fn kind_error(&self, op: &'static str, obj: usize) -> RstbError {
+        RstbError::Kind {
+            op,
+            object: full_name(obj).unwrap_or_default(),
+            kind: self.get_kind(obj),
+        }
+    }
 }
 
-impl sim_if::SimIf for Vhpi {
-    fn set_value_int(&self, handle: usize, value: i32) -> RstbResult<()> {
-        // TODO: Currently there is an issue with writing values via VHPI. Questa doesnt support
-        // waveform creation with VHPI enabled so it's hard to check whats wrong...
-        let mut val = new_vhpi_value();
-        val.set_int_val(value as u32);
-        // self.log(&format!("setting value = {:?}", val.format));
+impl SimIf for Vhpi {
+    fn set_value_int(&self, obj: usize, value: i32, mode: WriteMode) -> SimResult<()> {
+        match value_class(obj) {
+            ValueClass::Logic | ValueClass::LogicVec => {
+                let size = self.get_size(obj) as usize;
+                let bin = (0..size)
+                    .rev()
+                    .map(|i| match i < 32 && (value >> i) & 1 == 1 {
+                        true => '1',
+                        false => '0',
+                    })
+                    .collect();
+                self.set_value_bin(obj, bin, mode)
+            }
+            ValueClass::Enum => {
+                let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiEnumVal);
+                val.value.enumv = value as u32;
+                self._put_value("set_value_int", obj, &mut val, mode)
+            }
+            ValueClass::Int => {
+                let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiIntVal);
+                val.value.intg = value as u32;
+                self._put_value("set_value_int", obj, &mut val, mode)
+            }
+            _ => Err(self.kind_error("set_value_int", obj)),
+        }
+    }
+    fn get_value_int(&self, obj: usize) -> SimResult<i32> {
+        let format = match value_class(obj) {
+            ValueClass::Logic | ValueClass::LogicVec => {
+                // X, U, Z, W and - are read as 0
+                let bin = self.get_value_bin(obj)?;
+                let mut val = bin.chars().fold(0u32, |acc, c| {
+                    (acc << 1) | matches!(c, '1' | 'H' | 'h') as u32
+                });
+                // sign extend numeric_std signed vectors, like vpiIntVal does
+                let size = bin.len();
+                if (1..32).contains(&size) && self.is_signed(obj) && (val >> (size - 1)) & 1 == 1 {
+                    val |= u32::MAX << size;
+                }
+                return Ok(val as i32);
+            }
+            ValueClass::Enum => vhpi_user::vhpiFormatT_vhpiEnumVal,
+            ValueClass::Int => vhpi_user::vhpiFormatT_vhpiIntVal,
+            _ => return Err(self.kind_error("get_value_int", obj)),
+        };
+        let mut val = new_vhpi_value(format);
         unsafe {
-            match vhpi_user::vhpi_put_value(
-                handle as *mut u32,
-                &mut val,
-                vhpi_user::vhpiPutValueModeT_vhpiDepositPropagate,
-            ) {
-                0 => Ok(()),
-                _ => Err(RstbErr),
+            if vhpi_user::vhpi_get_value(obj as vhpi_user::vhpiHandleT, &mut val) != 0 {
+                return Err(error("get_value_int", obj));
+            }
+            match val.format {
+                vhpi_user::vhpiFormatT_vhpiEnumVal => Ok(val.value.enumv as i32),
+                vhpi_user::vhpiFormatT_vhpiIntVal => Ok(val.value.intg as i32),
+                _ => Err(self.kind_error("get_value_int", obj)),
+            }
+        }
+    }
+    fn set_value_bin(&self, obj: usize, value: String, mode: WriteMode) -> SimResult<()> {
+        let mut enums = value
+            .to_ascii_uppercase()
+            .chars()
+            .map(|c| {
+                STD_LOGIC_CHARS
+                    .iter()
+                    .position(|l| *l == c)
+                    .map(|p| p as u32)
+            })
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| RstbError::InvalidValue {
+                op: "set_value_bin",
+                object: full_name(obj).unwrap_or_default(),
+                value: value.clone(),
+            })?;
+        match value_class(obj) {
+            ValueClass::Logic => {
+                let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiLogicVal);
+                val.value.enumv = *enums.first().ok_or_else(|| RstbError::InvalidValue {
+                    op: "set_value_bin",
+                    object: full_name(obj).unwrap_or_default(),
+                    value: value.clone(),
+                })?;
+                self._put_value("set_value_bin", obj, &mut val, mode)
             }
+            ValueClass::LogicVec => {
+                let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiLogicVecVal);
+                val.numElems = enums.len() as i32;
+                val.bufSize = (enums.len() * std::mem::size_of::<u32>()) as vhpi_user::size_t;
+                val.value.enumvs = enums.as_mut_ptr();
+                self._put_value("set_value_bin", obj, &mut val, mode)
+            }
+            _ => Err(self.kind_error("set_value_bin", obj)),
         }
     }
-    fn get_value_int(&self, obj: usize) -> RstbResult<i32> {
+    fn get_value_bin(&self, obj: usize) -> SimResult<String> {
+        let size = self.get_size(obj).max(1) as usize;
+        let mut buf: Vec<u8> = vec![0; size + 1];
+        let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiBinStrVal);
+        val.bufSize = buf.len() as vhpi_user::size_t;
+        val.value.str_ = buf.as_mut_ptr() as *mut i8;
         unsafe {
-            let mut val = new_vhpi_value();
-            vhpi_user::vhpi_get_value(obj as *mut u32, &mut val);
-            if val.format == vhpi_user::vhpiFormatT_vhpiIntVal {
-                Ok(val.value.intg as i32)
-            } else {
-                Err(RstbErr)
+            if vhpi_user::vhpi_get_value(obj as vhpi_user::vhpiHandleT, &mut val) != 0 {
+                return Err(error("get_value_bin", obj));
+            }
+            Ok(CStr::from_ptr(val.value.str_)
+                .to_string_lossy()
+                .into_owned())
+        }
+    }
+    fn set_value_f64(&self, obj: usize, value: f64, mode: WriteMode) -> SimResult<()> {
+        match value_class(obj) {
+            ValueClass::Real => {
+                let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiRealVal);
+                val.value.real = value;
+                self._put_value("set_value_f64", obj, &mut val, mode)
             }
+            _ => Err(self.kind_error("set_value_f64", obj)),
+        }
+    }
+    fn get_value_f64(&self, obj: usize) -> SimResult<f64> {
+        let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiRealVal);
+        unsafe {
+            if vhpi_user::vhpi_get_value(obj as vhpi_user::vhpiHandleT, &mut val) != 0 {
+                return Err(error("get_value_f64", obj));
+            }
+            match val.format {
+                vhpi_user::vhpiFormatT_vhpiRealVal => Ok(val.value.real),
+                _ => Err(self.kind_error("get_value_f64", obj)),
+            }
+        }
+    }
+    fn set_value_str(&self, obj: usize, value: &str, mode: WriteMode) -> SimResult<()> {
+        if value_class(obj) != ValueClass::Str {
+            return Err(self.kind_error("set_value_str", obj));
+        }
+        let mut buf = value.as_bytes().to_vec();
+        buf.push(0);
+        let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiStrVal);
+        val.bufSize = buf.len() as vhpi_user::size_t;
+        val.value.str_ = buf.as_mut_ptr() as *mut i8;
+        self._put_value("set_value_str", obj, &mut val, mode)
+    }
+    fn get_value_str(&self, obj: usize) -> SimResult<String> {
+        let size = self.get_size(obj).max(0) as usize;
+        let mut buf: Vec<u8> = vec![0; size + 1];
+        let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiStrVal);
+        val.bufSize = buf.len() as vhpi_user::size_t;
+        val.value.str_ = buf.as_mut_ptr() as *mut i8;
+        unsafe {
+            if vhpi_user::vhpi_get_value(obj as vhpi_user::vhpiHandleT, &mut val) != 0 {
+                return Err(error("get_value_str", obj));
+            }
+            Ok(CStr::from_ptr(val.value.str_)
+                .to_string_lossy()
+                .into_owned())
+        }
+    }
+    fn release(&self, obj: usize) -> SimResult<()> {
+        let mut val = new_vhpi_value(vhpi_user::vhpiFormatT_vhpiObjTypeVal);
+        match unsafe {
+            vhpi_user::vhpi_put_value(
+                obj as vhpi_user::vhpiHandleT,
+                &mut val,
+                vhpi_user::vhpiPutValueModeT_vhpiRelease,
+            )
+        } {
+            0 => Ok(()),
+            _ => Err(error("release", obj)),
         }
     }
-    fn get_handle_by_name(&self, name: &str) -> RstbResult<usize> {
-        let mut name_string = name.to_string();
+    fn get_handle_by_name(&self, name: &str) -> SimResult<usize> {
+        // rstb separates hierarchy levels by '.', VHPI full names use ':'
+        let mut name_string = format!(":{}", name.replace('.', ":"));
         name_string.push('\0');
 
         let hdl = unsafe {
-            vhpi_user::vhpi_handle_by_name(
-                name_string.as_mut_str().as_mut_ptr() as *mut i8,
-                std::ptr::null_mut(),
-            )
+            vhpi_user::vhpi_handle_by_name(name_string.as_ptr() as *const i8, std::ptr::null_mut())
         };
         if hdl.is_null() {
-            self.log(&format!("Couldn't get handle from name {}", name));
-            Result::Err(RstbErr)
+            Err(RstbError::NotFound {
+                name: name.to_string(),
+            })
         } else {
-            Result::Ok(hdl as usize)
+            Ok(hdl as usize)
         }
     }
     fn get_sim_time_steps(&self) -> u64 {
@@ -60,224 +277,523 @@ impl sim_if::SimIf for Vhpi {
             ..Default::default()
         };
         unsafe {
-            vhpi_user::vhpi_get_time(&mut time_obj, std::ptr::null_mut() as *mut i64);
+            vhpi_user::vhpi_get_time(&mut time_obj, std::ptr::null_mut());
         }
         ((time_obj.high as u64) << 32) + time_obj.low as u64
     }
-    fn log(&self, s: &str) {
-        let mut string = format!("{} {}\n\0", self.get_sim_time_steps(), s);
-        // eprintln!("{}", string);
+    fn log(&self, msg: &str) {
+        let mut string = format!("{} {}\n\0", time_stamp(self.get_sim_time_f64("ns")), msg);
         unsafe { vhpi_user::vhpi_printf(string.as_mut_ptr() as *mut i8) };
     }
-    fn get_kind(&self, _obj: usize) -> ObjectKind {
-        // TODO !!
-        ObjectKind::BitVector(1)
+    fn get_size(&self, obj: usize) -> i32 {
+        match value_class(obj) {
+            ValueClass::Int => 32,
+            ValueClass::Other => 0,
+            _ => unsafe {
+                vhpi_user::vhpi_get(
+                    vhpi_user::vhpiIntPropertyT_vhpiSizeP,
+                    obj as vhpi_user::vhpiHandleT,
+                ) as i32
+            },
+        }
     }
-    fn get_full_name(&self, obj: usize) -> RstbResult<String> {
-        unsafe {
-            let ptr =
-                vhpi_user::vhpi_get_str(vhpi_user::vhpiStrPropertyT_vhpiFullNameP, obj as *mut u32);
-            let s = CStr::from_ptr(check_null(ptr)?)
-                .to_owned()
-                .into_string()
-                .unwrap();
-            Ok(s)
+    fn get_kind(&self, obj: usize) -> ObjectKind {
+        match get_kind_raw(obj) {
+            vhpi_user::vhpiClassKindT_vhpiGenericDeclK
+            | vhpi_user::vhpiClassKindT_vhpiConstDeclK => return ObjectKind::Param,
+            _ => (),
+        }
+        match value_class(obj) {
+            ValueClass::Logic | ValueClass::LogicVec | ValueClass::Enum | ValueClass::Int => {
+                ObjectKind::Bits
+            }
+            ValueClass::Real => ObjectKind::Real,
+            ValueClass::Str => ObjectKind::String,
+            ValueClass::Array => ObjectKind::Array,
+            ValueClass::Other => match get_kind_raw(obj) {
+                vhpi_user::vhpiClassKindT_vhpiRootInstK
+                | vhpi_user::vhpiClassKindT_vhpiCompInstStmtK => ObjectKind::Module,
+                vhpi_user::vhpiClassKindT_vhpiBlockStmtK
+                | vhpi_user::vhpiClassKindT_vhpiForGenerateK
+                | vhpi_user::vhpiClassKindT_vhpiIfGenerateK => ObjectKind::Scope,
+                _ => ObjectKind::Other,
+            },
+        }
+    }
+    fn is_signed(&self, obj: usize) -> bool {
+        match value_class(obj) {
+            ValueClass::Int => true,
+            ValueClass::LogicVec => base_type(obj)
+                .and_then(|t| get_str(vhpi_user::vhpiStrPropertyT_vhpiNameP, t as usize))
+                .is_some_and(|name| name.eq_ignore_ascii_case("signed")),
+            _ => false,
         }
     }
+    fn get_full_name(&self, obj: usize) -> SimResult<String> {
+        full_name(obj).ok_or_else(|| error("get_full_name", obj))
+    }
     fn get_sim_precision(&self) -> i8 {
-        let precision = unsafe {
-            vhpi_user::vhpi_get_phys(
-                vhpi_user::vhpiPhysPropertyT_vhpiResolutionLimitP,
-                std::ptr::null_mut(),
+        self.precision
+    }
+    fn get_root_handle(&self) -> SimResult<usize> {
+        let root = unsafe {
+            vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiRootInst, std::ptr::null_mut())
+        };
+        check_null(root)
+            .map(|r| r as usize)
+            .ok_or_else(|| error("get_root_handle", 0))
+    }
+    fn get_children(&self, obj: usize) -> Vec<usize> {
+        [
+            vhpi_user::vhpiOneToManyT_vhpiPortDecls,
+            vhpi_user::vhpiOneToManyT_vhpiSigDecls,
+            vhpi_user::vhpiOneToManyT_vhpiInternalRegions,
+        ]
+        .iter()
+        .flat_map(|t| iterate(*t, obj))
+        .collect()
+    }
+    fn get_ports(&self, obj: usize) -> Vec<(usize, PortDirection)> {
+        iterate(vhpi_user::vhpiOneToManyT_vhpiPortDecls, obj)
+            .into_iter()
+            .map(|port| {
+                let mode = unsafe {
+                    vhpi_user::vhpi_get(
+                        vhpi_user::vhpiIntPropertyT_vhpiModeP,
+                        port as vhpi_user::vhpiHandleT,
+                    )
+                };
+                let direction = match mode {
+                    vhpi_user::vhpiModeT_vhpiInMode => PortDirection::Input,
+                    vhpi_user::vhpiModeT_vhpiOutMode | vhpi_user::vhpiModeT_vhpiBufferMode => {
+                        PortDirection::Output
+                    }
+                    vhpi_user::vhpiModeT_vhpiInoutMode => PortDirection::Inout,
+                    _ => PortDirection::Other,
+                };
+                (port, direction)
+            })
+            .collect()
+    }
+    fn get_params(&self, obj: usize) -> Vec<usize> {
+        [
+            vhpi_user::vhpiOneToManyT_vhpiGenericDecls,
+            vhpi_user::vhpiOneToManyT_vhpiConstDecls,
+        ]
+        .iter()
+        .flat_map(|t| iterate(*t, obj))
+        .collect()
+    }
+    fn get_enum_literals(&self, obj: usize) -> Vec<(String, i64)> {
+        let base = match base_type(obj) {
+            Some(b) if type_kind(b) == vhpi_user::vhpiClassKindT_vhpiEnumTypeDeclK => b,
+            _ => return Vec::new(),
+        };
+        // values are read and written as position of the literal
+        iterate(vhpi_user::vhpiOneToManyT_vhpiEnumLiterals, base as usize)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(pos, lit)| {
+                Some((
+                    get_str(vhpi_user::vhpiStrPropertyT_vhpiNameP, lit)?,
+                    pos as i64,
+                ))
+            })
+            .collect()
+    }
+    fn get_handle_by_index(&self, obj: usize, index: i32) -> SimResult<usize> {
+        // VHPI indexes by offset from the left bound
+        let (left, right) = self.get_range(obj)?;
+        let offset = match left <= right {
+            true => index - left,
+            false => left - index,
+        };
+        if offset < 0 || offset > (left - right).abs() {
+            return Err(RstbError::NotFound {
+                name: format!("{}({})", full_name(obj).unwrap_or_default(), index),
+            });
+        }
+        let hdl = unsafe {
+            vhpi_user::vhpi_handle_by_index(
+                vhpi_user::vhpiOneToManyT_vhpiIndexedNames,
+                obj as vhpi_user::vhpiHandleT,
+                offset,
+            )
+        };
+        check_null(hdl)
+            .map(|h| h as usize)
+            .ok_or_else(|| error("get_handle_by_index", obj))
+    }
+    fn get_range(&self, obj: usize) -> SimResult<(i32, i32)> {
+        let subtype = unsafe {
+            vhpi_user::vhpi_handle(
+                vhpi_user::vhpiOneToOneT_vhpiType,
+                obj as vhpi_user::vhpiHandleT,
             )
         };
-        let mut precision_int = ((precision.high as i64) << 32) + (precision.low as i64);
-        if precision_int > 2 {
-            precision_int = 2;
+        let subtype = check_null(subtype).ok_or_else(|| error("get_range", obj))? as usize;
+        let range = *iterate(vhpi_user::vhpiOneToManyT_vhpiConstraints, subtype)
+            .first()
+            .ok_or_else(|| self.kind_error("get_range", obj))?
+            as vhpi_user::vhpiHandleT;
+        unsafe {
+            Ok((
+                vhpi_user::vhpi_get(vhpi_user::vhpiIntPropertyT_vhpiLeftBoundP, range) as i32,
+                vhpi_user::vhpi_get(vhpi_user::vhpiIntPropertyT_vhpiRightBoundP, range) as i32,
+            ))
         }
-        if precision_int < -15 {
-            precision_int = -15;
+    }
+    fn register_callback_rw(&self) -> SimResult<usize> {
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbLastKnownDeltaCycle,
+                None,
+                std::ptr::null_mut(),
+                react_vhpi_rw,
+            )
         }
-        precision_int as i8
     }
-    fn get_root_handle(&self) -> RstbResult<usize> {
-        let root = unsafe {
-            vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiRootInst, std::ptr::null_mut())
+    fn register_callback_ro(&self) -> SimResult<usize> {
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbEndOfTimeStep,
+                None,
+                std::ptr::null_mut(),
+                react_vhpi_ro,
+            )
+        }
+    }
+    fn register_callback_time(&self, t: u64) -> SimResult<usize> {
+        let time = vhpi_user::vhpiTimeT {
+            high: (t >> 32) as u32,
+            low: (t & 0xFFFF_FFFF) as u32,
         };
-        Ok(root as usize)
-    }
-    fn register_callback(&self, cb: SimCallback) -> RstbResult<usize> {
-        #[allow(unused_assignments)]  // time is actually read by simulator
-        let mut time = vhpi_user::vhpiTimeT{..Default::default()};
-        let mut cb_data = match cb {
-            SimCallback::ReadOnly => vhpi_user::vhpiCbDataT {
-                reason: vhpi_user::vhpiCbEndOfTimeStep as i32,
-                cb_rtn: Some(react_vhpi),
-                ..Default::default()
-            },
-            SimCallback::ReadWrite => vhpi_user::vhpiCbDataT {
-                reason: vhpi_user::vhpiCbLastKnownDeltaCycle as i32,
-                cb_rtn: Some(react_vhpi),
-                ..Default::default()
-            },
-            SimCallback::Time(t) => {
-                // self.log(&format!("Registering timer: t={}", t));
-                time = vhpi_user::vhpiTimeT {
-                    high: (t >> 32) as u32,
-                    low: (t & 0xFFFF_FFFF) as u32,
-                };
-                // self.log(&format!("time={:?}", time));
-
-                vhpi_user::vhpiCbDataT {
-                    reason: vhpi_user::vhpiCbAfterDelay as i32,
-                    time: &mut time,
-                    cb_rtn: Some(react_vhpi),
-                    ..Default::default()
-                }
-            },
-            SimCallback::Edge(hdl) => {
-
-                SIM_IF.log("registering edge cb");
-                vhpi_user::vhpiCbDataT {
-                reason: vhpi_user::vhpiCbValueChange as i32,
-                obj: hdl as *mut u32,
-                cb_rtn: Some(react_vhpi),
-                ..Default::default()
-            }},
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbAfterDelay,
+                Some(time),
+                std::ptr::null_mut(),
+                react_vhpi_time,
+            )
+        }
+    }
+    fn register_callback_next_time_step(&self) -> SimResult<usize> {
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbNextTimeStep,
+                None,
+                std::ptr::null_mut(),
+                react_vhpi_next_time_step,
+            )
+        }
+    }
+    fn register_callback_start_of_time_step(&self, t: u64) -> SimResult<usize> {
+        let time = vhpi_user::vhpiTimeT {
+            high: (t >> 32) as u32,
+            low: (t & 0xFFFF_FFFF) as u32,
         };
-        let cb_hdl =
-            unsafe { vhpi_user::vhpi_register_cb(&mut cb_data, vhpi_user::vhpiReturnCb as i32) };
-
-        // SIM_IF.log(&format!("cb_hdl = {}", cb_hdl as usize));
-        Ok(cb_hdl as usize)
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbTimeOut,
+                Some(time),
+                std::ptr::null_mut(),
+                react_vhpi_start_of_time_step,
+            )
+        }
     }
-    fn cancel_callback(&self, cb_hdl: usize) -> RstbResult<()> {
-        match unsafe { vhpi_user::vhpi_remove_cb(cb_hdl as *mut u32) } {
+    fn register_callback_nba(&self) -> SimResult<usize> {
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbEndOfProcesses,
+                None,
+                std::ptr::null_mut(),
+                react_vhpi_nba,
+            )
+        }
+    }
+    fn register_callback_edge(&self, sig_hdl: usize) -> SimResult<usize> {
+        unsafe {
+            self._register_callback(
+                vhpi_user::vhpiCbValueChange,
+                None,
+                sig_hdl as vhpi_user::vhpiHandleT,
+                react_vhpi_edge,
+            )
+        }
+    }
+    fn cancel_callback(&self, cb_hdl: usize) -> SimResult<()> {
+        match unsafe { vhpi_user::vhpi_remove_cb(cb_hdl as vhpi_user::vhpiHandleT) } {
             0 => Ok(()),
-            _ => Err(RstbErr),
+            _ => Err(RstbError::sim("cancel_callback", "", last_error())),
         }
     }
+    fn control(&self, command: SimControl) -> SimResult<()> {
+        let command = match command {
+            SimControl::Stop => vhpi_user::vhpiSimControlT_vhpiStop,
+            SimControl::Finish => vhpi_user::vhpiSimControlT_vhpiFinish,
+            SimControl::Reset => vhpi_user::vhpiSimControlT_vhpiReset,
+        };
+        match unsafe { vhpi_user::vhpi_control(command) } {
+            0 => Ok(()),
+            _ => Err(RstbError::sim("control", "", last_error())),
+        }
+    }
+    fn product(&self) -> String {
+        tool()
+            .and_then(|tool| get_str(vhpi_user::vhpiStrPropertyT_vhpiNameP, tool))
+            .unwrap_or_default()
+    }
+    fn version(&self) -> String {
+        tool()
+            .and_then(|tool| get_str(vhpi_user::vhpiStrPropertyT_vhpiToolVersionP, tool))
+            .unwrap_or_default()
+    }
+    fn args(&self) -> Vec<String> {
+        let tool = match tool() {
+            Some(tool) => tool,
+            None => return Vec::new(),
+        };
+        iterate(vhpi_user::vhpiOneToManyT_vhpiArgvs, tool)
+            .into_iter()
+            .filter_map(|arg| get_str(vhpi_user::vhpiStrPropertyT_vhpiStrValP, arg))
+            .collect()
+    }
 }
 
 #[no_mangle]
-extern "C" fn react_vhpi(cb_data: *const vhpi_user::vhpiCbDataS) {
-    let cb = unsafe {
-        if (*cb_data).reason == vhpi_user::vhpiCbValueChange as i32 {
-            SIM_IF.log("reacting to value change");
+pub(crate) extern "C" fn react_vhpi_edge(cb_data: *const vhpi_user::vhpiCbDataT) {
+    let hdl = unsafe { (*cb_data).obj as usize };
+    let vhpi = get_interface(Interface::Vhpi);
+    let mut edge = EdgeKind::Any;
+    if vhpi.get_kind(hdl) == ObjectKind::Bits && vhpi.get_size(hdl) == 1 {
+        // a value which can't be read is no rising or falling edge
+        edge = match vhpi.get_value_int(hdl) {
+            Ok(0) => EdgeKind::Falling,
+            Ok(_) => EdgeKind::Rising,
+            Err(_) => EdgeKind::Any,
         };
-        (*cb_data)
-            .to_sim_callback()
-            .expect("Invalid callback data received.")
+    }
+    trigger::react_edge(hdl, edge);
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vhpi_time(_: *const vhpi_user::vhpiCbDataT) {
+    // Not every simulator fills in the callback time, but the callback is executed at the requested time.
+    trigger::react_time(SIM_IF.get_sim_time_steps());
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vhpi_next_time_step(_: *const vhpi_user::vhpiCbDataT) {
+    trigger::react_next_time_step();
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vhpi_start_of_time_step(_: *const vhpi_user::vhpiCbDataT) {
+    trigger::react_start_of_time_step(SIM_IF.get_sim_time_steps());
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vhpi_nba(_: *const vhpi_user::vhpiCbDataT) {
+    trigger::react_nba();
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vhpi_ro(_: *const vhpi_user::vhpiCbDataT) {
+    trigger::react_ro();
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vhpi_rw(_: *const vhpi_user::vhpiCbDataT) {
+    trigger::react_rw();
+}
+
+fn get_time_precision() -> i8 {
+    // resolution limit is given in fs
+    let limit = unsafe {
+        vhpi_user::vhpi_get_phys(
+            vhpi_user::vhpiPhysPropertyT_vhpiResolutionLimitP,
+            std::ptr::null_mut(),
+        )
     };
-    // vpi::log(&format!("Converted CB: {:?}", cb));
+    let fs = ((limit.high as u64) << 32) + limit.low as u64;
+    let precision = (fs.max(1) as f64).log10().round() as i32 - 15;
+    precision.clamp(-15, 2) as i8
+}
 
-    match cb {
-        SimCallback::Edge(hdl) => {
-            // if signal is not a scalar, schedule all callbacks, since there is no "rising" on a multi bit signal
-            let mut edge = EdgeKind::Any;
-            let mut is_scalar = true;
-            if let sim_if::ObjectKind::BitVector(size) = sim_if::SIM_IF.get_kind(hdl) {
-                if size != 1 {
-                    is_scalar = false;
-                }
+fn tool() -> Option<usize> {
+    let tool =
+        unsafe { vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiTool, std::ptr::null_mut()) };
+    match tool.is_null() {
+        true => None,
+        false => Some(tool as usize),
+    }
+}
+
+fn get_kind_raw(obj: usize) -> vhpi_user::vhpiClassKindT {
+    unsafe {
+        vhpi_user::vhpi_get(
+            vhpi_user::vhpiIntPropertyT_vhpiKindP,
+            obj as vhpi_user::vhpiHandleT,
+        )
+    }
+}
+
+fn iterate(relation: vhpi_user::vhpiOneToManyT, obj: usize) -> Vec<usize> {
+    let mut list = Vec::new();
+    unsafe {
+        let iterator = vhpi_user::vhpi_iterator(relation, obj as vhpi_user::vhpiHandleT);
+        if iterator.is_null() {
+            return list;
+        }
+        loop {
+            let hdl = vhpi_user::vhpi_scan(iterator);
+            match hdl.is_null() {
+                true => break,
+                _ => list.push(hdl as usize),
+            };
+        }
+    }
+    list
+}
+
+fn value_class(obj: usize) -> ValueClass {
+    match get_kind_raw(obj) {
+        vhpi_user::vhpiClassKindT_vhpiSigDeclK
+        | vhpi_user::vhpiClassKindT_vhpiPortDeclK
+        | vhpi_user::vhpiClassKindT_vhpiVarDeclK
+        | vhpi_user::vhpiClassKindT_vhpiConstDeclK
+        | vhpi_user::vhpiClassKindT_vhpiGenericDeclK
+        | vhpi_user::vhpiClassKindT_vhpiIndexedNameK
+        | vhpi_user::vhpiClassKindT_vhpiSelectedNameK => (),
+        _ => return ValueClass::Other,
+    }
+    let base = match base_type(obj) {
+        Some(base) => base,
+        None => return ValueClass::Other,
+    };
+    match type_kind(base) {
+        vhpi_user::vhpiClassKindT_vhpiEnumTypeDeclK => match is_logic_type(base) {
+            true => ValueClass::Logic,
+            false => ValueClass::Enum,
+        },
+        vhpi_user::vhpiClassKindT_vhpiIntTypeDeclK => ValueClass::Int,
+        vhpi_user::vhpiClassKindT_vhpiFloatTypeDeclK => ValueClass::Real,
+        vhpi_user::vhpiClassKindT_vhpiArrayTypeDeclK => {
+            let mut elem =
+                unsafe { vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiElemType, base) };
+            if elem.is_null() {
+                elem = unsafe {
+                    vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiElemSubtype, base)
+                };
             }
-            if is_scalar {
-                unsafe {
-                    if !(*cb_data).value.is_null() {
-                        // this actually happens under some conditions?
-                        edge = match (*(*cb_data).value).value.intg {
-                            0 => EdgeKind::Falling,
-                            _ => EdgeKind::Rising,
-                        };
-                    }
+            let elem_base = match elem.is_null() {
+                true => None,
+                false => base_type(elem as usize),
+            };
+            match elem_base {
+                Some(e)
+                    if type_kind(e) == vhpi_user::vhpiClassKindT_vhpiEnumTypeDeclK
+                        && is_logic_type(e) =>
+                {
+                    ValueClass::LogicVec
+                }
+                Some(e)
+                    if get_str(vhpi_user::vhpiStrPropertyT_vhpiNameP, e as usize)
+                        .is_some_and(|name| name.eq_ignore_ascii_case("character")) =>
+                {
+                    ValueClass::Str
                 }
+                _ => ValueClass::Array,
             }
-            trigger::react(cb, Some(edge));
         }
-        _ => trigger::react(cb, None),
+        _ => ValueClass::Other,
     }
 }
 
-impl vhpi_user::vhpiCbDataS {
-    pub fn to_sim_callback(&self) -> Option<SimCallback> {
-        const CB_AFTER_DELAY: i32 = vhpi_user::vhpiCbAfterDelay as i32;
-        const CB_VALUE_CHANGE: i32 = vhpi_user::vhpiCbValueChange as i32;
-        const CB_READ_WRITE: i32 = vhpi_user::vhpiCbLastKnownDeltaCycle as i32;
-        const CB_READ_ONLY: i32 = vhpi_user::vhpiCbEndOfTimeStep as i32;
-        match self.reason {
-            CB_AFTER_DELAY => Some(SimCallback::Time(unsafe {
-                // SIM_IF.log("to_sim_callback() after delay:");
-                // SIM_IF.log(&format!("*self.time={:?}", *self.time));
-
-                (u64::from((*self.time).high) << 32) + u64::from((*self.time).low)
-            })),
-            CB_VALUE_CHANGE => Some(SimCallback::Edge(self.obj as usize)),
-            CB_READ_WRITE => Some(SimCallback::ReadWrite),
-            CB_READ_ONLY => Some(SimCallback::ReadOnly),
-            _ => None,
+fn base_type(obj: usize) -> Option<vhpi_user::vhpiHandleT> {
+    let obj = obj as vhpi_user::vhpiHandleT;
+    unsafe {
+        let base = vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiBaseType, obj);
+        if !base.is_null() {
+            return Some(base);
+        }
+        // objects or subtypes that don't reference their base type directly
+        let subtype = vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiType, obj);
+        if subtype.is_null() {
+            return None;
+        }
+        let base = vhpi_user::vhpi_handle(vhpi_user::vhpiOneToOneT_vhpiBaseType, subtype);
+        match base.is_null() {
+            true => Some(subtype),
+            false => Some(base),
         }
     }
 }
 
-// fn is_array(handle: usize) -> bool {
-//     // let val = unsafe { vpi_user::vpi_get(vpi_user::vpiArray as i32, handle as *mut u32) };
-//     let val = unsafe { vpi_user::vpi_get(17, handle as *mut u32) };
-//     let r = matches!(val, 0);
-//     r
-// }
-
-// pub fn print_vpi_cb_data(cb_data: *mut vpi_user::t_cb_data) {
-//     unsafe {
-//         eprintln!("##################################");
-//         eprintln!("print_cb_data");
-//         eprintln!("addr: {:p}", cb_data);
-//         eprintln!("cb_data: {:?}", *cb_data);
-//         if !(*cb_data).time.is_null() {
-//             eprintln!("time: {:?}", *(*cb_data).time);
-//         }
-//         eprintln!("cb_rtn: {:?}", (*cb_data).cb_rtn);
-//         eprintln!("##################################");
-//     }
-// }
+fn type_kind(type_hdl: vhpi_user::vhpiHandleT) -> u32 {
+    unsafe { vhpi_user::vhpi_get(vhpi_user::vhpiIntPropertyT_vhpiKindP, type_hdl) }
+}
 
-// fn discover_nets(handle: usize) -> Vec<usize> {
-//     unsafe {
-//         let iterator = vpi_user::vpi_iterate(vpi_user::vpiNet as i32, handle as *mut u32);
-//         let mut list = Vec::new();
-//         loop {
-//             let obj = vpi_user::vpi_scan(iterator);
-//             match obj.is_null() {
-//                 true => break,
-//                 _ => list.push(obj as usize),
-//             };
-//         }
-//         list
-//     }
-// }
+fn is_logic_type(type_hdl: vhpi_user::vhpiHandleT) -> bool {
+    get_str(vhpi_user::vhpiStrPropertyT_vhpiNameP, type_hdl as usize).is_some_and(|name| {
+        let name = name.to_ascii_uppercase();
+        name == "STD_ULOGIC" || name == "STD_LOGIC" || name == "BIT"
+    })
+}
 
-fn new_vhpi_value() -> vhpi_user::vhpiValueT {
-    vhpi_user::vhpiValueT {
-        format: vhpi_user::vhpiFormatT_vhpiIntVal,
-        bufSize: 4,
-        numElems: 0,
-        unit: vhpi_user::vhpiPhysT { high: 0, low: 0 },
-        value: vhpi_user::vhpiValueS__bindgen_ty_1 { intg: 0 },
+fn get_str(property: vhpi_user::vhpiStrPropertyT, obj: usize) -> Option<String> {
+    unsafe {
+        let ptr = vhpi_user::vhpi_get_str(property, obj as vhpi_user::vhpiHandleT);
+        let s = CStr::from_ptr(check_null(ptr)?)
+            .to_owned()
+            .into_string()
+            .ok()?;
+        Some(s)
     }
 }
 
-impl vhpi_user::vhpiValueT {
-    fn set_int_val(&mut self, val: u32) {
-        self.value = vhpi_user::vhpiValueS__bindgen_ty_1 { intg: val };
-        self.format = vhpi_user::vhpiFormatT_vhpiIntVal;
+fn new_vhpi_value(format: vhpi_user::vhpiFormatT) -> vhpi_user::vhpiValueT {
+    vhpi_user::vhpiValueT {
+        format,
+        ..Default::default()
     }
 }
 
 #[inline]
-fn check_null<T>(ptr: *const T) -> RstbResult<*const T> {
+fn check_null<T>(ptr: *const T) -> Option<*const T> {
     if ptr.is_null() {
-        Err(RstbErr)
+        None
     } else {
-        Ok(ptr)
+        Some(ptr)
     }
 }
 
+// Full name of `obj` in rstb notation, separated by '.'
+fn full_name(obj: usize) -> Option<String> {
+    let name = get_str(vhpi_user::vhpiStrPropertyT_vhpiFullNameP, obj)?;
+    Some(name.trim_start_matches(':').replace(':', "."))
+}
+
+// Message of the last VHPI call, if it failed
+fn last_error() -> Option<String> {
+    let mut info = vhpi_user::vhpiErrorInfoT::default();
+    unsafe {
+        match vhpi_user::vhpi_check_error(&mut info) {
+            0 => None,
+            _ => match info.message.is_null() {
+                true => None,
+                false => Some(CStr::from_ptr(info.message).to_string_lossy().into_owned()),
+            },
+        }
+    }
+}
+
+// Error of a failed `op` on `obj`, the message has to be read before any other VHPI call
+fn error(op: &'static str, obj: usize) -> RstbError {
+    let message = last_error();
+    let object = match obj {
+        0 => String::new(),
+        _ => full_name(obj).unwrap_or_default(),
+    };
+    RstbError::sim(op, object, message)
+}
@@ -1,11 +1,23 @@
 /* automatically generated by rust-bindgen 0.58.1 */
 
+pub const vhpiU: u32 = 0;
+pub const vhpiX: u32 = 1;
+pub const vhpi0: u32 = 2;
+pub const vhpi1: u32 = 3;
+pub const vhpiZ: u32 = 4;
+pub const vhpiW: u32 = 5;
+pub const vhpiL: u32 = 6;
+pub const vhpiH: u32 = 7;
+pub const vhpiDontCare: u32 = 8;
 pub const vhpiCbValueChange: u32 = 1001;
 pub const vhpiCbAfterDelay: u32 = 1010;
+pub const vhpiCbNextTimeStep: u32 = 1012;
+pub const vhpiCbEndOfProcesses: u32 = 1018;
 pub const vhpiCbLastKnownDeltaCycle: u32 = 1020;
 pub const vhpiCbEndOfTimeStep: u32 = 1024;
 pub const vhpiCbStartOfSimulation: u32 = 1034;
 pub const vhpiCbEndOfSimulation: u32 = 1035;
+pub const vhpiCbTimeOut: u32 = 1047;
 pub const vhpiReturnCb: u32 = 1;
 pub type size_t = ::std::os::raw::c_ulong;
 pub type __uint8_t = ::std::os::raw::c_uchar;
@@ -109,6 +121,129 @@ impl Default for vhpiValueS {
     }
 }
 pub type vhpiValueT = vhpiValueS;
+pub const vhpiClassKindT_vhpiAccessTypeDeclK: vhpiClassKindT = 1001;
+pub const vhpiClassKindT_vhpiAggregateK: vhpiClassKindT = 1002;
+pub const vhpiClassKindT_vhpiAliasDeclK: vhpiClassKindT = 1003;
+pub const vhpiClassKindT_vhpiAllK: vhpiClassKindT = 1004;
+pub const vhpiClassKindT_vhpiAllocatorK: vhpiClassKindT = 1005;
+pub const vhpiClassKindT_vhpiAnyCollectionK: vhpiClassKindT = 1006;
+pub const vhpiClassKindT_vhpiArchBodyK: vhpiClassKindT = 1007;
+pub const vhpiClassKindT_vhpiArgvK: vhpiClassKindT = 1008;
+pub const vhpiClassKindT_vhpiArrayTypeDeclK: vhpiClassKindT = 1009;
+pub const vhpiClassKindT_vhpiAssertStmtK: vhpiClassKindT = 1010;
+pub const vhpiClassKindT_vhpiAssocElemK: vhpiClassKindT = 1011;
+pub const vhpiClassKindT_vhpiAttrDeclK: vhpiClassKindT = 1012;
+pub const vhpiClassKindT_vhpiAttrSpecK: vhpiClassKindT = 1013;
+pub const vhpiClassKindT_vhpiBinaryExprK: vhpiClassKindT = 1014;
+pub const vhpiClassKindT_vhpiBitStringLiteralK: vhpiClassKindT = 1015;
+pub const vhpiClassKindT_vhpiBlockConfigK: vhpiClassKindT = 1016;
+pub const vhpiClassKindT_vhpiBlockStmtK: vhpiClassKindT = 1017;
+pub const vhpiClassKindT_vhpiBranchK: vhpiClassKindT = 1018;
+pub const vhpiClassKindT_vhpiCallbackK: vhpiClassKindT = 1019;
+pub const vhpiClassKindT_vhpiCaseStmtK: vhpiClassKindT = 1020;
+pub const vhpiClassKindT_vhpiCharLiteralK: vhpiClassKindT = 1021;
+pub const vhpiClassKindT_vhpiCompConfigK: vhpiClassKindT = 1022;
+pub const vhpiClassKindT_vhpiCompDeclK: vhpiClassKindT = 1023;
+pub const vhpiClassKindT_vhpiCompInstStmtK: vhpiClassKindT = 1024;
+pub const vhpiClassKindT_vhpiCondSigAssignStmtK: vhpiClassKindT = 1025;
+pub const vhpiClassKindT_vhpiCondWaveformK: vhpiClassKindT = 1026;
+pub const vhpiClassKindT_vhpiConfigDeclK: vhpiClassKindT = 1027;
+pub const vhpiClassKindT_vhpiConstDeclK: vhpiClassKindT = 1028;
+pub const vhpiClassKindT_vhpiConstParamDeclK: vhpiClassKindT = 1029;
+pub const vhpiClassKindT_vhpiConvFuncK: vhpiClassKindT = 1030;
+pub const vhpiClassKindT_vhpiDerefObjK: vhpiClassKindT = 1031;
+pub const vhpiClassKindT_vhpiDisconnectSpecK: vhpiClassKindT = 1032;
+pub const vhpiClassKindT_vhpiDriverK: vhpiClassKindT = 1033;
+pub const vhpiClassKindT_vhpiDriverCollectionK: vhpiClassKindT = 1034;
+pub const vhpiClassKindT_vhpiElemAssocK: vhpiClassKindT = 1035;
+pub const vhpiClassKindT_vhpiElemDeclK: vhpiClassKindT = 1036;
+pub const vhpiClassKindT_vhpiEntityClassEntryK: vhpiClassKindT = 1037;
+pub const vhpiClassKindT_vhpiEntityDeclK: vhpiClassKindT = 1038;
+pub const vhpiClassKindT_vhpiEnumLiteralK: vhpiClassKindT = 1039;
+pub const vhpiClassKindT_vhpiEnumRangeK: vhpiClassKindT = 1040;
+pub const vhpiClassKindT_vhpiEnumTypeDeclK: vhpiClassKindT = 1041;
+pub const vhpiClassKindT_vhpiExitStmtK: vhpiClassKindT = 1042;
+pub const vhpiClassKindT_vhpiFileDeclK: vhpiClassKindT = 1043;
+pub const vhpiClassKindT_vhpiFileParamDeclK: vhpiClassKindT = 1044;
+pub const vhpiClassKindT_vhpiFileTypeDeclK: vhpiClassKindT = 1045;
+pub const vhpiClassKindT_vhpiFloatRangeK: vhpiClassKindT = 1046;
+pub const vhpiClassKindT_vhpiFloatTypeDeclK: vhpiClassKindT = 1047;
+pub const vhpiClassKindT_vhpiForGenerateK: vhpiClassKindT = 1048;
+pub const vhpiClassKindT_vhpiForLoopK: vhpiClassKindT = 1049;
+pub const vhpiClassKindT_vhpiForeignfK: vhpiClassKindT = 1050;
+pub const vhpiClassKindT_vhpiFuncCallK: vhpiClassKindT = 1051;
+pub const vhpiClassKindT_vhpiFuncDeclK: vhpiClassKindT = 1052;
+pub const vhpiClassKindT_vhpiGenericDeclK: vhpiClassKindT = 1053;
+pub const vhpiClassKindT_vhpiGroupDeclK: vhpiClassKindT = 1054;
+pub const vhpiClassKindT_vhpiGroupTempDeclK: vhpiClassKindT = 1055;
+pub const vhpiClassKindT_vhpiIfGenerateK: vhpiClassKindT = 1056;
+pub const vhpiClassKindT_vhpiIfStmtK: vhpiClassKindT = 1057;
+pub const vhpiClassKindT_vhpiInPortK: vhpiClassKindT = 1058;
+pub const vhpiClassKindT_vhpiIndexedNameK: vhpiClassKindT = 1059;
+pub const vhpiClassKindT_vhpiIntLiteralK: vhpiClassKindT = 1060;
+pub const vhpiClassKindT_vhpiIntRangeK: vhpiClassKindT = 1061;
+pub const vhpiClassKindT_vhpiIntTypeDeclK: vhpiClassKindT = 1062;
+pub const vhpiClassKindT_vhpiIteratorK: vhpiClassKindT = 1063;
+pub const vhpiClassKindT_vhpiLibraryDeclK: vhpiClassKindT = 1064;
+pub const vhpiClassKindT_vhpiLoopStmtK: vhpiClassKindT = 1065;
+pub const vhpiClassKindT_vhpiNextStmtK: vhpiClassKindT = 1066;
+pub const vhpiClassKindT_vhpiNullLiteralK: vhpiClassKindT = 1067;
+pub const vhpiClassKindT_vhpiNullStmtK: vhpiClassKindT = 1068;
+pub const vhpiClassKindT_vhpiOperatorK: vhpiClassKindT = 1069;
+pub const vhpiClassKindT_vhpiOthersK: vhpiClassKindT = 1070;
+pub const vhpiClassKindT_vhpiOutPortK: vhpiClassKindT = 1071;
+pub const vhpiClassKindT_vhpiPackBodyK: vhpiClassKindT = 1072;
+pub const vhpiClassKindT_vhpiPackDeclK: vhpiClassKindT = 1073;
+pub const vhpiClassKindT_vhpiPackInstK: vhpiClassKindT = 1074;
+pub const vhpiClassKindT_vhpiParamAttrNameK: vhpiClassKindT = 1075;
+pub const vhpiClassKindT_vhpiPhysLiteralK: vhpiClassKindT = 1076;
+pub const vhpiClassKindT_vhpiPhysRangeK: vhpiClassKindT = 1077;
+pub const vhpiClassKindT_vhpiPhysTypeDeclK: vhpiClassKindT = 1078;
+pub const vhpiClassKindT_vhpiPortDeclK: vhpiClassKindT = 1079;
+pub const vhpiClassKindT_vhpiProcCallStmtK: vhpiClassKindT = 1080;
+pub const vhpiClassKindT_vhpiProcDeclK: vhpiClassKindT = 1081;
+pub const vhpiClassKindT_vhpiProcessStmtK: vhpiClassKindT = 1082;
+pub const vhpiClassKindT_vhpiProtectedTypeK: vhpiClassKindT = 1083;
+pub const vhpiClassKindT_vhpiProtectedTypeBodyK: vhpiClassKindT = 1084;
+pub const vhpiClassKindT_vhpiProtectedTypeDeclK: vhpiClassKindT = 1085;
+pub const vhpiClassKindT_vhpiRealLiteralK: vhpiClassKindT = 1086;
+pub const vhpiClassKindT_vhpiRecordTypeDeclK: vhpiClassKindT = 1087;
+pub const vhpiClassKindT_vhpiReportStmtK: vhpiClassKindT = 1088;
+pub const vhpiClassKindT_vhpiReturnStmtK: vhpiClassKindT = 1089;
+pub const vhpiClassKindT_vhpiRootInstK: vhpiClassKindT = 1090;
+pub const vhpiClassKindT_vhpiSelectSigAssignStmtK: vhpiClassKindT = 1091;
+pub const vhpiClassKindT_vhpiSelectWaveformK: vhpiClassKindT = 1092;
+pub const vhpiClassKindT_vhpiSelectedNameK: vhpiClassKindT = 1093;
+pub const vhpiClassKindT_vhpiSigDeclK: vhpiClassKindT = 1094;
+pub const vhpiClassKindT_vhpiSigParamDeclK: vhpiClassKindT = 1095;
+pub const vhpiClassKindT_vhpiSimpAttrNameK: vhpiClassKindT = 1096;
+pub const vhpiClassKindT_vhpiSimpleSigAssignStmtK: vhpiClassKindT = 1097;
+pub const vhpiClassKindT_vhpiSliceNameK: vhpiClassKindT = 1098;
+pub const vhpiClassKindT_vhpiStringLiteralK: vhpiClassKindT = 1099;
+pub const vhpiClassKindT_vhpiSubpBodyK: vhpiClassKindT = 1100;
+pub const vhpiClassKindT_vhpiSubtypeDeclK: vhpiClassKindT = 1101;
+pub const vhpiClassKindT_vhpiSubtypeIndicK: vhpiClassKindT = 1102;
+pub const vhpiClassKindT_vhpiToolK: vhpiClassKindT = 1103;
+pub const vhpiClassKindT_vhpiTransactionK: vhpiClassKindT = 1104;
+pub const vhpiClassKindT_vhpiTypeConvK: vhpiClassKindT = 1105;
+pub const vhpiClassKindT_vhpiUnaryExprK: vhpiClassKindT = 1106;
+pub const vhpiClassKindT_vhpiUnitDeclK: vhpiClassKindT = 1107;
+pub const vhpiClassKindT_vhpiUserAttrNameK: vhpiClassKindT = 1108;
+pub const vhpiClassKindT_vhpiVarAssignStmtK: vhpiClassKindT = 1109;
+pub const vhpiClassKindT_vhpiVarDeclK: vhpiClassKindT = 1110;
+pub const vhpiClassKindT_vhpiVarParamDeclK: vhpiClassKindT = 1111;
+pub const vhpiClassKindT_vhpiWaitStmtK: vhpiClassKindT = 1112;
+pub const vhpiClassKindT_vhpiWaveformElemK: vhpiClassKindT = 1113;
+pub const vhpiClassKindT_vhpiWhileLoopK: vhpiClassKindT = 1114;
+pub const vhpiClassKindT_vhpiQualifiedExprK: vhpiClassKindT = 1115;
+pub const vhpiClassKindT_vhpiUseClauseK: vhpiClassKindT = 1116;
+pub const vhpiClassKindT_vhpiVerilog: vhpiClassKindT = 1117;
+pub const vhpiClassKindT_vhpiEdifUnit: vhpiClassKindT = 1118;
+pub const vhpiClassKindT_vhpiCollectionK: vhpiClassKindT = 1119;
+pub const vhpiClassKindT_vhpiVHDL: vhpiClassKindT = 1120;
+pub const vhpiClassKindT_vhpiSystemC: vhpiClassKindT = 1121;
+#[doc = " OBJECT KINDS"]
+pub type vhpiClassKindT = ::std::os::raw::c_uint;
 pub const vhpiOneToOneT_vhpiAbstractLiteral: vhpiOneToOneT = 1301;
 pub const vhpiOneToOneT_vhpiActual: vhpiOneToOneT = 1302;
 pub const vhpiOneToOneT_vhpiAll: vhpiOneToOneT = 1303;
@@ -370,6 +505,12 @@ pub const vhpiPhysPropertyT_vhpiSimTimeUnitP: vhpiPhysPropertyT = 1656;
 pub const vhpiPhysPropertyT_vhpiResolutionLimitP: vhpiPhysPropertyT = 1657;
 #[doc = " PHYSICAL PROPERTIES"]
 pub type vhpiPhysPropertyT = ::std::os::raw::c_uint;
+pub const vhpiModeT_vhpiInMode: vhpiModeT = 1001;
+pub const vhpiModeT_vhpiOutMode: vhpiModeT = 1002;
+pub const vhpiModeT_vhpiInoutMode: vhpiModeT = 1003;
+pub const vhpiModeT_vhpiBufferMode: vhpiModeT = 1004;
+pub const vhpiModeT_vhpiLinkageMode: vhpiModeT = 1005;
+pub type vhpiModeT = ::std::os::raw::c_uint;
 pub const vhpiSeverityT_vhpiNote: vhpiSeverityT = 1;
 pub const vhpiSeverityT_vhpiWarning: vhpiSeverityT = 2;
 pub const vhpiSeverityT_vhpiError: vhpiSeverityT = 3;
@@ -422,6 +563,13 @@ extern "C" {
         scope: vhpiHandleT,
     ) -> vhpiHandleT;
 }
+extern "C" {
+    pub fn vhpi_handle_by_index(
+        itRel: vhpiOneToManyT,
+        parent: vhpiHandleT,
+        indx: i32,
+    ) -> vhpiHandleT;
+}
 extern "C" {
     pub fn vhpi_handle(type_: vhpiOneToOneT, referenceHandle: vhpiHandleT) -> vhpiHandleT;
 }
@@ -460,6 +608,13 @@ extern "C" {
 extern "C" {
     pub fn vhpi_get_time(time_p: *mut vhpiTimeT, cycles: *mut ::std::os::raw::c_long);
 }
+pub const vhpiSimControlT_vhpiStop: vhpiSimControlT = 0;
+pub const vhpiSimControlT_vhpiFinish: vhpiSimControlT = 1;
+pub const vhpiSimControlT_vhpiReset: vhpiSimControlT = 2;
+pub type vhpiSimControlT = ::std::os::raw::c_uint;
+extern "C" {
+    pub fn vhpi_control(command: vhpiSimControlT, ...) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn vhpi_printf(format: *const ::std::os::raw::c_char, ...) -> ::std::os::raw::c_int;
 }
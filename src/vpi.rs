@@ -1,10 +1,15 @@
-use num_format::{Locale, ToFormattedString};
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 
-use crate::sim_if::{ObjectKind, SimCallback, SimIf, SIM_IF};
+use crate::quirks::quirks;
+use crate::sim_if::{
+    get_interface, time_stamp, Interface, ObjectKind, PortDirection, SimCallback, SimControl,
+    SimIf, VecWord, WriteMode,
+};
+use crate::systf::{self, SysTfKind};
 use crate::trigger;
 use crate::trigger::EdgeKind;
-use crate::{sv_vpi_user, vpi_user, SimpleResult};
+use crate::value::Val;
+use crate::{sv_vpi_user, vpi_user, RstbError, SimResult};
 
 pub(crate) struct Vpi {
     precision: i8,
@@ -38,29 +43,42 @@ impl Vpi {
         // vpi::log("Registeried callback with simulator.");
         vpi_user::vpi_register_cb(&mut cb_data) as usize
     }
-}
-
-impl SimIf for Vpi {
-    fn set_value_int(&self, obj: usize, value: i32, force: bool) -> SimpleResult<()> {
-        let mut val = vpi_user::t_vpi_value {
-            format: vpi_user::vpiIntVal as i32,
-            value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: value },
-        };
+    #[inline]
+    fn _put_value(
+        &self,
+        op: &'static str,
+        obj: usize,
+        mut val: vpi_user::t_vpi_value,
+        mode: WriteMode,
+    ) -> SimResult<()> {
         let mut time = vpi_user::t_vpi_time {
             type_: vpi_user::vpiSimTime as i32,
             ..Default::default()
         };
-        let mut flag = vpi_user::vpiInertialDelay as i32;
-        if force {
-            flag = vpi_user::vpiForceFlag as i32;
-        }
+        let flag = match mode {
+            WriteMode::NoDelay => vpi_user::vpiNoDelay,
+            // with zero delay, Deposit, Inertial and Transport only differ in how rstb treats
+            // pending delayed writes
+            WriteMode::Deposit | WriteMode::Inertial => vpi_user::vpiInertialDelay,
+            WriteMode::Transport => vpi_user::vpiTransportDelay,
+            WriteMode::Force => vpi_user::vpiForceFlag,
+        } as i32;
         unsafe {
             vpi_user::vpi_put_value(obj as *mut u32, &mut val, &mut time, flag);
         };
-        // TODO: error??
-        Ok(())
+        check_error(op, obj)
+    }
+}
+
+impl SimIf for Vpi {
+    fn set_value_int(&self, obj: usize, value: i32, mode: WriteMode) -> SimResult<()> {
+        let val = vpi_user::t_vpi_value {
+            format: vpi_user::vpiIntVal as i32,
+            value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: value },
+        };
+        self._put_value("set_value_int", obj, val, mode)
     }
-    fn get_value_int(&self, obj: usize) -> SimpleResult<i32> {
+    fn get_value_int(&self, obj: usize) -> SimResult<i32> {
         unsafe {
             let mut val = vpi_user::t_vpi_value {
                 format: vpi_user::vpiIntVal as i32,
@@ -70,52 +88,126 @@ impl SimIf for Vpi {
             if val.format == vpi_user::vpiIntVal as i32 {
                 Ok(val.value.integer)
             } else {
-                Err(())
+                Err(error("get_value_int", obj))
             }
         }
     }
-    fn set_value_bin(&self, obj: usize, value: String, force: bool) -> SimpleResult<()> {
+    fn set_value_bin(&self, obj: usize, value: String, mode: WriteMode) -> SimResult<()> {
         let mut val = value;
         val.push('\0');
-        let mut val = vpi_user::t_vpi_value {
+        let vpi_val = vpi_user::t_vpi_value {
             format: vpi_user::vpiBinStrVal as i32,
             value: vpi_user::t_vpi_value__bindgen_ty_1 {
                 str_: val.as_mut_ptr() as *mut i8,
             },
         };
-        let mut time = vpi_user::t_vpi_time {
-            type_: vpi_user::vpiSimTime as i32,
-            ..Default::default()
-        };
-        let mut flag = vpi_user::vpiInertialDelay as i32;
-        if force {
-            flag = vpi_user::vpiForceFlag as i32;
+        self._put_value("set_value_bin", obj, vpi_val, mode)
+    }
+    fn get_value_bin(&self, obj: usize) -> SimResult<String> {
+        unsafe {
+            let mut val = vpi_user::t_vpi_value {
+                format: vpi_user::vpiBinStrVal as i32,
+                value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: 0 },
+            };
+            vpi_user::vpi_get_value(obj as *mut u32, &mut val);
+            if val.format == vpi_user::vpiBinStrVal as i32 {
+                let ptr = check_null(val.value.str_).ok_or_else(|| error("get_value_bin", obj))?;
+                Ok(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            } else {
+                Err(error("get_value_bin", obj))
+            }
         }
+    }
+    fn set_value_f64(&self, obj: usize, value: f64, mode: WriteMode) -> SimResult<()> {
+        let val = vpi_user::t_vpi_value {
+            format: vpi_user::vpiRealVal as i32,
+            value: vpi_user::t_vpi_value__bindgen_ty_1 { real: value },
+        };
+        self._put_value("set_value_f64", obj, val, mode)
+    }
+    fn get_value_f64(&self, obj: usize) -> SimResult<f64> {
         unsafe {
-            vpi_user::vpi_put_value(obj as *mut u32, &mut val, &mut time, flag);
+            let mut val = vpi_user::t_vpi_value {
+                format: vpi_user::vpiRealVal as i32,
+                value: vpi_user::t_vpi_value__bindgen_ty_1 { real: 0.0 },
+            };
+            vpi_user::vpi_get_value(obj as *mut u32, &mut val);
+            if val.format == vpi_user::vpiRealVal as i32 {
+                Ok(val.value.real)
+            } else {
+                Err(error("get_value_f64", obj))
+            }
+        }
+    }
+    fn set_value_str(&self, obj: usize, value: &str, mode: WriteMode) -> SimResult<()> {
+        let mut val = value.to_string();
+        val.push('\0');
+        let vpi_val = vpi_user::t_vpi_value {
+            format: vpi_user::vpiStringVal as i32,
+            value: vpi_user::t_vpi_value__bindgen_ty_1 {
+                str_: val.as_mut_ptr() as *mut i8,
+            },
         };
-        // TODO: error??
-        Ok(())
+        self._put_value("set_value_str", obj, vpi_val, mode)
     }
-    fn get_value_bin(&self, obj: usize) -> SimpleResult<String> {
+    fn get_value_str(&self, obj: usize) -> SimResult<String> {
         unsafe {
             let mut val = vpi_user::t_vpi_value {
-                format: vpi_user::vpiBinStrVal as i32,
+                format: vpi_user::vpiStringVal as i32,
                 value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: 0 },
             };
             vpi_user::vpi_get_value(obj as *mut u32, &mut val);
-            if val.format == vpi_user::vpiBinStrVal as i32 {
-                let s = CStr::from_ptr(check_null(val.value.str_)?)
-                    .to_owned()
-                    .into_string()
-                    .unwrap();
-                Ok(s)
+            if val.format == vpi_user::vpiStringVal as i32 {
+                let ptr = check_null(val.value.str_).ok_or_else(|| error("get_value_str", obj))?;
+                Ok(CStr::from_ptr(ptr).to_string_lossy().into_owned())
             } else {
-                Err(())
+                Err(error("get_value_str", obj))
+            }
+        }
+    }
+    fn set_value_vec(&self, obj: usize, value: &[VecWord], mode: WriteMode) -> SimResult<()> {
+        let n_words = (get_size_raw(obj).max(1) as usize).div_ceil(32);
+        let mut vector: Vec<vpi_user::t_vpi_vecval> = (0..n_words)
+            .map(|i| {
+                let w = value.get(i).copied().unwrap_or_default();
+                vpi_user::t_vpi_vecval {
+                    aval: w.aval as i32,
+                    bval: w.bval as i32,
+                }
+            })
+            .collect();
+        let val = vpi_user::t_vpi_value {
+            format: vpi_user::vpiVectorVal as i32,
+            value: vpi_user::t_vpi_value__bindgen_ty_1 {
+                vector: vector.as_mut_ptr(),
+            },
+        };
+        self._put_value("set_value_vec", obj, val, mode)
+    }
+    fn get_value_vec(&self, obj: usize) -> SimResult<Vec<VecWord>> {
+        let n_words = (get_size_raw(obj).max(1) as usize).div_ceil(32);
+        unsafe {
+            let mut val = vpi_user::t_vpi_value {
+                format: vpi_user::vpiVectorVal as i32,
+                value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: 0 },
+            };
+            vpi_user::vpi_get_value(obj as *mut u32, &mut val);
+            if val.format != vpi_user::vpiVectorVal as i32 {
+                return Err(error("get_value_vec", obj));
             }
+            // the simulator owns the buffer, copy before the next vpi call
+            let ptr = check_null(val.value.vector).ok_or_else(|| error("get_value_vec", obj))?;
+            let vector = std::slice::from_raw_parts(ptr, n_words);
+            Ok(vector
+                .iter()
+                .map(|v| VecWord {
+                    aval: v.aval as u32,
+                    bval: v.bval as u32,
+                })
+                .collect())
         }
     }
-    fn release(&self, obj: usize) -> SimpleResult<()> {
+    fn release(&self, obj: usize) -> SimResult<()> {
         let mut val = vpi_user::t_vpi_value {
             format: vpi_user::vpiIntVal as i32,
             value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: 0 },
@@ -128,9 +220,9 @@ impl SimIf for Vpi {
                 vpi_user::vpiReleaseFlag as i32,
             );
         };
-        Ok(())
+        check_error("release", obj)
     }
-    fn get_handle_by_name(&self, name: &str) -> SimpleResult<usize> {
+    fn get_handle_by_name(&self, name: &str) -> SimResult<usize> {
         let mut name_string = name.to_string();
         name_string.push('\0');
 
@@ -141,12 +233,14 @@ impl SimIf for Vpi {
             ) as usize
         };
         if hdl == 0 {
-            self.log(&format!("Couldn't get handle from name {}", name));
-            Result::Err(())
+            Err(RstbError::NotFound {
+                name: name.to_string(),
+            })
         } else {
-            Result::Ok(hdl)
+            Ok(hdl)
         }
     }
+    #[allow(clippy::unnecessary_cast)]
     fn get_sim_time_steps(&self) -> u64 {
         let mut time_obj = vpi_user::t_vpi_time {
             type_: vpi_user::vpiSimTime as i32,
@@ -158,16 +252,7 @@ impl SimIf for Vpi {
         ((time_obj.high as u64) << 32) + time_obj.low as u64
     }
     fn log(&self, msg: &str) {
-        let t = self.get_sim_time_f64("ns");
-        let int = t.floor() as u64;
-        let mut frac_str = format!("{:.3}", t % 1.0);
-        frac_str.remove(0);
-        let mut string = format!(
-            "{}{}ns {}\n\0",
-            int.to_formatted_string(&Locale::en),
-            frac_str,
-            msg
-        );
+        let mut string = format!("{} {}\n\0", time_stamp(self.get_sim_time_f64("ns")), msg);
         // eprintln!("{}", string);
         unsafe { vpi_user::vpi_printf(string.as_mut_ptr() as *mut i8) };
     }
@@ -178,51 +263,58 @@ impl SimIf for Vpi {
         let t = get_kind_raw(obj);
         match t as u32 {
             vpi_user::vpiRealVar | sv_vpi_user::vpiShortRealVar => ObjectKind::Real,
+            sv_vpi_user::vpiStringVar => ObjectKind::String,
             vpi_user::vpiNet
+            | vpi_user::vpiNetBit
             | vpi_user::vpiReg
+            | vpi_user::vpiRegBit
+            | vpi_user::vpiMemoryWord
             | vpi_user::vpiIntegerVar
             | sv_vpi_user::vpiBitVar
             | sv_vpi_user::vpiLongIntVar
-            | sv_vpi_user::vpiIntVar => ObjectKind::Bits,
+            | sv_vpi_user::vpiIntVar
+            | sv_vpi_user::vpiEnumVar
+            | sv_vpi_user::vpiEnumNet => ObjectKind::Bits,
+            vpi_user::vpiMemory | vpi_user::vpiNetArray | vpi_user::vpiRegArray => {
+                ObjectKind::Array
+            }
+            vpi_user::vpiModule => ObjectKind::Module,
+            vpi_user::vpiParameter | vpi_user::vpiSpecParam => ObjectKind::Param,
+            vpi_user::vpiGenScope | vpi_user::vpiNamedBegin | vpi_user::vpiNamedFork => {
+                ObjectKind::Scope
+            }
             _ => ObjectKind::Other,
         }
     }
     fn is_signed(&self, obj_handle: usize) -> bool {
         (unsafe { vpi_user::vpi_get(vpi_user::vpiSigned as i32, obj_handle as *mut u32) } != 0)
     }
-    fn get_full_name(&self, obj: usize) -> SimpleResult<String> {
-        unsafe {
-            let ptr = vpi_user::vpi_get_str(vpi_user::vpiFullName as i32, obj as *mut u32);
-            let s = CStr::from_ptr(check_null(ptr)?)
-                .to_owned()
-                .into_string()
-                .unwrap();
-            Ok(s)
-        }
+    fn get_full_name(&self, obj: usize) -> SimResult<String> {
+        full_name(obj).ok_or_else(|| error("get_full_name", obj))
     }
     fn get_sim_precision(&self) -> i8 {
         self.precision
     }
-    fn get_root_handle(&self) -> SimpleResult<usize> {
+    fn get_root_handle(&self) -> SimResult<usize> {
         let mut result: usize;
         let iterator =
             unsafe { vpi_user::vpi_iterate(vpi_user::vpiModule as i32, std::ptr::null_mut()) };
         if iterator.is_null() {
             unsafe { vpi_user::vpi_free_object(iterator) };
-            return Err(());
+            return Err(error("get_root_handle", 0));
         }
         let root = unsafe { vpi_user::vpi_scan(iterator) };
         if root.is_null() {
             unsafe { vpi_user::vpi_free_object(iterator) };
-            return Err(());
+            return Err(error("get_root_handle", 0));
         }
         result = root as usize;
 
         let name = match self.get_full_name(root as usize) {
             Ok(string) => string,
-            Err(_) => {
+            Err(e) => {
                 unsafe { vpi_user::vpi_free_object(iterator) };
-                return Err(()); // Cannot get module's name => consider it invalid
+                return Err(e); // Cannot get module's name => consider it invalid
             }
         };
 
@@ -237,13 +329,80 @@ impl SimIf for Vpi {
                 result = othermod as usize;
             } else {
                 unsafe { vpi_user::vpi_free_object(iterator) };
-                return Err(());
+                return Err(error("get_root_handle", 0));
             }
         }
         unsafe { vpi_user::vpi_free_object(iterator) }; // Free memory used by the iterator
         Ok(result)
     }
-    fn register_callback_rw(&self) -> SimpleResult<usize> {
+    fn get_children(&self, obj: usize) -> Vec<usize> {
+        // simulators differ in which relations they support and what they include, e.g. regs
+        // may or may not be part of vpiVariables
+        [
+            vpi_user::vpiNet,
+            vpi_user::vpiReg,
+            vpi_user::vpiVariables,
+            vpi_user::vpiIntegerVar,
+            vpi_user::vpiRealVar,
+            vpi_user::vpiMemory,
+            vpi_user::vpiNetArray,
+            vpi_user::vpiRegArray,
+            vpi_user::vpiModule,
+            vpi_user::vpiInternalScope,
+        ]
+        .iter()
+        .flat_map(|t| iterate(*t, obj))
+        .collect()
+    }
+    fn get_ports(&self, obj: usize) -> Vec<(usize, PortDirection)> {
+        iterate(vpi_user::vpiPort, obj)
+            .into_iter()
+            .filter_map(|port| {
+                let mut name = get_name(port)?;
+                name.push('\0');
+                let direction = match unsafe {
+                    vpi_user::vpi_get(vpi_user::vpiDirection as i32, port as *mut u32)
+                } as u32
+                {
+                    vpi_user::vpiInput => PortDirection::Input,
+                    vpi_user::vpiOutput => PortDirection::Output,
+                    vpi_user::vpiInout => PortDirection::Inout,
+                    _ => PortDirection::Other,
+                };
+                let hdl = unsafe {
+                    vpi_user::vpi_handle_by_name(name.as_mut_ptr() as *mut i8, obj as *mut u32)
+                };
+                check_null(hdl).map(|h| (h as usize, direction))
+            })
+            .collect()
+    }
+    fn get_params(&self, obj: usize) -> Vec<usize> {
+        iterate(vpi_user::vpiParameter, obj)
+    }
+    fn get_enum_literals(&self, obj: usize) -> Vec<(String, i64)> {
+        let typespec =
+            unsafe { vpi_user::vpi_handle(sv_vpi_user::vpiTypespec as i32, obj as *mut u32) };
+        if typespec.is_null() {
+            return Vec::new();
+        }
+        iterate(sv_vpi_user::vpiEnumConst, typespec as usize)
+            .into_iter()
+            .filter_map(|c| Some((get_name(c)?, self.get_value_int(c).ok()? as i64)))
+            .collect()
+    }
+    fn get_handle_by_index(&self, obj: usize, index: i32) -> SimResult<usize> {
+        let hdl = unsafe { vpi_user::vpi_handle_by_index(obj as *mut u32, index) };
+        check_null(hdl)
+            .map(|h| h as usize)
+            .ok_or_else(|| error("get_handle_by_index", obj))
+    }
+    fn get_range(&self, obj: usize) -> SimResult<(i32, i32)> {
+        Ok((
+            range_bound(vpi_user::vpiLeftRange, obj)?,
+            range_bound(vpi_user::vpiRightRange, obj)?,
+        ))
+    }
+    fn register_callback_rw(&self) -> SimResult<usize> {
         const reason: i32 = vpi_user::cbReadWriteSynch as i32;
         let time = vpi_user::t_vpi_time {
             type_: vpi_user::vpiSuppressTime as i32,
@@ -256,7 +415,7 @@ impl SimIf for Vpi {
         let handle = std::ptr::null_mut();
         Ok(unsafe { self._register_callback(reason, time, value, handle, react_vpi_rw) })
     }
-    fn register_callback_ro(&self) -> SimpleResult<usize> {
+    fn register_callback_ro(&self) -> SimResult<usize> {
         const reason: i32 = vpi_user::cbReadOnlySynch as i32;
         let time = vpi_user::t_vpi_time {
             type_: vpi_user::vpiSuppressTime as i32,
@@ -269,7 +428,7 @@ impl SimIf for Vpi {
         let handle = std::ptr::null_mut();
         Ok(unsafe { self._register_callback(reason, time, value, handle, react_vpi_ro) })
     }
-    fn register_callback_time(&self, t: u64) -> SimpleResult<usize> {
+    fn register_callback_time(&self, t: u64) -> SimResult<usize> {
         const reason: i32 = vpi_user::cbAfterDelay as i32;
         let time = vpi_user::t_vpi_time {
             type_: vpi_user::vpiSimTime as i32,
@@ -284,41 +443,140 @@ impl SimIf for Vpi {
         let handle = std::ptr::null_mut();
         Ok(unsafe { self._register_callback(reason, time, value, handle, react_vpi_time) })
     }
-    fn register_callback_edge(&self, sig_hdl: usize) -> SimpleResult<usize> {
+    fn register_callback_next_time_step(&self) -> SimResult<usize> {
+        const reason: i32 = vpi_user::cbNextSimTime as i32;
+        let time = vpi_user::t_vpi_time {
+            type_: vpi_user::vpiSimTime as i32,
+            ..Default::default()
+        };
+        let value = vpi_user::t_vpi_value {
+            format: vpi_user::vpiSuppressVal as i32,
+            ..Default::default()
+        };
+        let handle = std::ptr::null_mut();
+        Ok(unsafe {
+            self._register_callback(reason, time, value, handle, react_vpi_next_time_step)
+        })
+    }
+    fn register_callback_start_of_time_step(&self, t: u64) -> SimResult<usize> {
+        const reason: i32 = vpi_user::cbAtStartOfSimTime as i32;
+        // unlike cbAfterDelay, the time is absolute
+        let abs_time = t + self.get_sim_time_steps();
+        let time = vpi_user::t_vpi_time {
+            type_: vpi_user::vpiSimTime as i32,
+            high: (abs_time >> 32) as u32,
+            low: (abs_time & 0xFFFF_FFFF) as u32,
+            ..Default::default()
+        };
+        let value = vpi_user::t_vpi_value {
+            format: vpi_user::vpiSuppressVal as i32,
+            ..Default::default()
+        };
+        let handle = std::ptr::null_mut();
+        Ok(unsafe {
+            self._register_callback(reason, time, value, handle, react_vpi_start_of_time_step)
+        })
+    }
+    fn register_callback_nba(&self) -> SimResult<usize> {
+        const reason: i32 = vpi_user::cbNBASynch as i32;
+        let time = vpi_user::t_vpi_time {
+            type_: vpi_user::vpiSuppressTime as i32,
+            ..Default::default()
+        };
+        let value = vpi_user::t_vpi_value {
+            format: vpi_user::vpiSuppressVal as i32,
+            ..Default::default()
+        };
+        let handle = std::ptr::null_mut();
+        // cbNBASynch was added by SystemVerilog, not every simulator supports it
+        match unsafe { self._register_callback(reason, time, value, handle, react_vpi_nba) } {
+            0 => Err(RstbError::sim("register_callback_nba", "", last_error())),
+            cb_hdl => Ok(cb_hdl),
+        }
+    }
+    fn register_callback_edge(&self, sig_hdl: usize) -> SimResult<usize> {
         const reason: i32 = vpi_user::cbValueChange as i32;
         let time = vpi_user::t_vpi_time {
             type_: vpi_user::vpiSuppressTime as i32,
             ..Default::default()
         };
+        // real and string valued signals are reported as such, everything else as integer to
+        // detect edges
+        let format = match self.get_kind(sig_hdl) {
+            ObjectKind::Real => vpi_user::vpiRealVal,
+            ObjectKind::String => vpi_user::vpiStringVal,
+            _ => vpi_user::vpiIntVal,
+        };
         let value = vpi_user::t_vpi_value {
-            format: vpi_user::vpiIntVal as i32,
+            format: format as i32,
             ..Default::default()
         };
         let handle = sig_hdl as *mut u32;
         Ok(unsafe { self._register_callback(reason, time, value, handle, react_vpi_edge) })
     }
-    fn cancel_callback(&self, cb_hdl: usize) -> SimpleResult<()> {
+    fn cancel_callback(&self, cb_hdl: usize) -> SimResult<()> {
         match unsafe { vpi_user::vpi_remove_cb(cb_hdl as *mut u32) } {
             1 => Ok(()),
-            _ => Err(()),
+            _ => Err(RstbError::sim("cancel_callback", "", last_error())),
+        }
+    }
+    fn control(&self, command: SimControl) -> SimResult<()> {
+        let operation = match command {
+            SimControl::Stop => vpi_user::vpiStop,
+            SimControl::Finish => vpi_user::vpiFinish,
+            SimControl::Reset => vpi_user::vpiReset,
+        };
+        // diagnostic level 1: print simulation time and location
+        let diag_level: vpi_user::PLI_INT32 = 1;
+        match unsafe { vpi_user::vpi_control(operation as i32, diag_level) } {
+            1 => Ok(()),
+            _ => Err(RstbError::sim("control", "", last_error())),
+        }
+    }
+    fn args(&self) -> Vec<String> {
+        let info = match vlog_info() {
+            Some(info) if !info.argv.is_null() => info,
+            _ => return Vec::new(),
+        };
+        unsafe {
+            (0..info.argc as usize)
+                .filter_map(|i| check_null(*info.argv.add(i)))
+                .map(|arg| CStr::from_ptr(arg).to_string_lossy().into_owned())
+                .collect()
         }
     }
+    fn product(&self) -> String {
+        vlog_info()
+            .and_then(|info| unsafe { cstr_to_string(info.product) })
+            .unwrap_or_default()
+    }
+    fn version(&self) -> String {
+        vlog_info()
+            .and_then(|info| unsafe { cstr_to_string(info.version) })
+            .unwrap_or_default()
+    }
 }
 
 #[no_mangle]
 pub(crate) extern "C" fn react_vpi_edge(cb_data: *mut vpi_user::t_cb_data) -> vpi_user::PLI_INT32 {
     let hdl = unsafe { (*cb_data).obj as usize };
     let mut edge = EdgeKind::Any;
-    if SIM_IF.get_size(hdl) == 1 {
-        unsafe {
-            if !(*cb_data).value.is_null() {
-                // this actually happens under some conditions?
-                edge = match (*(*cb_data).value).value.integer {
-                    0 => EdgeKind::Falling,
-                    _ => EdgeKind::Rising,
+    if get_size_raw(hdl) == 1 {
+        let value = unsafe {
+            match (*cb_data).value.as_ref() {
+                Some(v) if v.format == vpi_user::vpiIntVal as i32 => Some(v.value.integer),
+                _ if quirks().edge_value_missing => {
+                    get_interface(Interface::Vpi).get_value_int(hdl).ok()
                 }
+                _ => None,
             }
         };
+        if let Some(value) = value {
+            edge = match value {
+                0 => EdgeKind::Falling,
+                _ => EdgeKind::Rising,
+            }
+        }
     }
     trigger::react_edge(hdl, edge);
     0
@@ -331,6 +589,29 @@ pub(crate) extern "C" fn react_vpi_time(cb_data: *mut vpi_user::t_cb_data) -> vp
     0
 }
 
+#[no_mangle]
+pub(crate) extern "C" fn react_vpi_next_time_step(
+    _: *mut vpi_user::t_cb_data,
+) -> vpi_user::PLI_INT32 {
+    trigger::react_next_time_step();
+    0
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vpi_start_of_time_step(
+    cb_data: *mut vpi_user::t_cb_data,
+) -> vpi_user::PLI_INT32 {
+    let t = unsafe { (*cb_data).decode_time() };
+    trigger::react_start_of_time_step(t);
+    0
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn react_vpi_nba(_: *mut vpi_user::t_cb_data) -> vpi_user::PLI_INT32 {
+    trigger::react_nba();
+    0
+}
+
 #[no_mangle]
 pub(crate) extern "C" fn react_vpi_ro(_: *mut vpi_user::t_cb_data) -> vpi_user::PLI_INT32 {
     trigger::react_ro();
@@ -343,6 +624,125 @@ pub(crate) extern "C" fn react_vpi_rw(_: *mut vpi_user::t_cb_data) -> vpi_user::
     0
 }
 
+#[no_mangle]
+pub(crate) extern "C" fn react_vpi_calltf(
+    user_data: *mut vpi_user::PLI_BYTE8,
+) -> vpi_user::PLI_INT32 {
+    let id = user_data as usize;
+    let call = unsafe {
+        vpi_user::vpi_handle(vpi_user::vpiSysTfCall as i32, std::ptr::null_mut()) as usize
+    };
+    let args = iterate(vpi_user::vpiArgument, call)
+        .into_iter()
+        .map(arg_value)
+        .collect();
+    let ret = systf::call(id, args);
+    if systf::kind(id) != SysTfKind::Task {
+        put_return_value(call, ret);
+    }
+    0
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn vpi_sizetf(user_data: *mut vpi_user::PLI_BYTE8) -> vpi_user::PLI_INT32 {
+    match systf::kind(user_data as usize) {
+        SysTfKind::SizedFunc(size) => size,
+        _ => 32,
+    }
+}
+
+pub(crate) fn register_systf(name: &str, kind: SysTfKind, id: usize) -> SimResult<()> {
+    let (type_, sysfunctype) = match kind {
+        SysTfKind::Task => (vpi_user::vpiSysTask, 0),
+        SysTfKind::IntFunc => (vpi_user::vpiSysFunc, vpi_user::vpiIntFunc),
+        SysTfKind::RealFunc => (vpi_user::vpiSysFunc, vpi_user::vpiRealFunc),
+        SysTfKind::SizedFunc(_) => (vpi_user::vpiSysFunc, vpi_user::vpiSizedFunc),
+    };
+    let mut data = vpi_user::t_vpi_systf_data {
+        type_: type_ as i32,
+        sysfunctype: sysfunctype as i32,
+        // not every simulator copies the name, so it is never freed
+        tfname: CString::new(name).unwrap().into_raw(),
+        calltf: Some(react_vpi_calltf),
+        sizetf: match kind {
+            SysTfKind::SizedFunc(_) => Some(vpi_sizetf),
+            _ => None,
+        },
+        user_data: id as *mut vpi_user::PLI_BYTE8,
+        ..Default::default()
+    };
+    match check_null(unsafe { vpi_user::vpi_register_systf(&mut data) }) {
+        Some(_) => Ok(()),
+        None => Err(RstbError::sim("register_systf", name, last_error())),
+    }
+}
+
+// Value of a system task argument, in the representation that fits its type
+fn arg_value(arg: usize) -> Val {
+    let vpi = get_interface(Interface::Vpi);
+    let handle = arg as *mut u32;
+    let (type_, const_type) = unsafe {
+        let type_ = vpi_user::vpi_get(vpi_user::vpiType as i32, handle) as u32;
+        match type_ {
+            vpi_user::vpiConstant => (
+                type_,
+                vpi_user::vpi_get(vpi_user::vpiConstType as i32, handle) as u32,
+            ),
+            _ => (type_, 0),
+        }
+    };
+    let value = match (type_, const_type) {
+        (vpi_user::vpiConstant, vpi_user::vpiStringConst) => {
+            vpi.get_value_str(arg).map(Val::String)
+        }
+        (vpi_user::vpiConstant, vpi_user::vpiRealConst) | (vpi_user::vpiRealVar, _) => {
+            vpi.get_value_f64(arg).map(Val::Float)
+        }
+        _ if get_size_raw(arg) <= 32 => vpi.get_value_int(arg).map(|v| match vpi.is_signed(arg) {
+            true => Val::Signed(v),
+            false => Val::Int(v as u32),
+        }),
+        _ => vpi
+            .get_value_bin(arg)
+            .map(|bin| bin.parse().map(Val::Logic).unwrap_or(Val::BitStr(bin))),
+    };
+    value.unwrap_or(Val::Error)
+}
+
+fn put_return_value(call: usize, ret: Val) {
+    let vpi = get_interface(Interface::Vpi);
+    let mode = WriteMode::NoDelay;
+    let result = match ret {
+        Val::Int(v) => vpi.set_value_int(call, v as i32, mode),
+        Val::Signed(v) => vpi.set_value_int(call, v, mode),
+        Val::Float(v) => vpi.set_value_f64(call, v, mode),
+        Val::BitStr(bin) => vpi.set_value_bin(call, bin, mode),
+        // VPI only knows 4-state values
+        Val::Logic(l) => {
+            vpi.set_value_bin(call, l.to_x01z().to_string().to_ascii_lowercase(), mode)
+        }
+        Val::String(v) => vpi.set_value_str(call, &v, mode),
+        _ => return,
+    };
+    if let Err(e) = result {
+        trigger::fail_in_callback(&format!("Could not return value of system function: {}", e));
+    }
+}
+
+fn vlog_info() -> Option<vpi_user::t_vpi_vlog_info> {
+    let mut info = vpi_user::t_vpi_vlog_info::default();
+    match unsafe { vpi_user::vpi_get_vlog_info(&mut info) } {
+        1 => Some(info),
+        _ => None,
+    }
+}
+
+unsafe fn cstr_to_string(ptr: *mut vpi_user::PLI_BYTE8) -> Option<String> {
+    let ptr = check_null(ptr)?;
+    Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+#[allow(clippy::manual_clamp)]
 fn get_time_precision() -> i8 {
     let mut precision =
         unsafe { vpi_user::vpi_get(vpi_user::vpiTimePrecision as i32, std::ptr::null_mut()) };
@@ -362,11 +762,15 @@ impl vpi_user::t_cb_data {
         // }
         (u64::from((*self.time).high) << 32) + u64::from((*self.time).low)
     }
+    #[allow(clippy::wrong_self_convention)]
     pub(crate) fn to_sim_callback(&self) -> Option<SimCallback> {
         const CB_AFTER_DELAY: i32 = vpi_user::cbAfterDelay as i32;
         const CB_VALUE_CHANGE: i32 = vpi_user::cbValueChange as i32;
         const CB_READ_WRITE: i32 = vpi_user::cbReadWriteSynch as i32;
         const CB_READ_ONLY: i32 = vpi_user::cbReadOnlySynch as i32;
+        const CB_NEXT_SIM_TIME: i32 = vpi_user::cbNextSimTime as i32;
+        const CB_AT_START_OF_SIM_TIME: i32 = vpi_user::cbAtStartOfSimTime as i32;
+        const CB_NBA: i32 = vpi_user::cbNBASynch as i32;
         match self.reason {
             CB_AFTER_DELAY => Some(SimCallback::Time(unsafe {
                 (u64::from((*self.time).high) << 32) + u64::from((*self.time).low)
@@ -374,6 +778,11 @@ impl vpi_user::t_cb_data {
             CB_VALUE_CHANGE => Some(SimCallback::Edge(self.obj as usize)),
             CB_READ_WRITE => Some(SimCallback::ReadWrite),
             CB_READ_ONLY => Some(SimCallback::ReadOnly),
+            CB_NEXT_SIM_TIME => Some(SimCallback::NextTimeStep),
+            CB_AT_START_OF_SIM_TIME => Some(SimCallback::StartOfTimeStep(unsafe {
+                (u64::from((*self.time).high) << 32) + u64::from((*self.time).low)
+            })),
+            CB_NBA => Some(SimCallback::Nba),
             _ => None,
         }
     }
@@ -411,7 +820,7 @@ pub fn get_kind_raw(obj: usize) -> i32 {
 fn get_name(obj: usize) -> Option<String> {
     unsafe {
         let ptr = vpi_user::vpi_get_str(vpi_user::vpiName as i32, obj as *mut u32);
-        let s = CStr::from_ptr(check_null(ptr).ok()?)
+        let s = CStr::from_ptr(check_null(ptr)?)
             .to_owned()
             .into_string()
             .unwrap();
@@ -420,9 +829,34 @@ fn get_name(obj: usize) -> Option<String> {
 }
 
 pub fn discover_nets(handle: usize) -> Vec<usize> {
+    iterate(vpi_user::vpiNet, handle)
+}
+
+fn range_bound(relation: u32, obj: usize) -> SimResult<i32> {
     unsafe {
-        let iterator = vpi_user::vpi_iterate(vpi_user::vpiNet as i32, handle as *mut u32);
-        let mut list = Vec::new();
+        let expr = check_null(vpi_user::vpi_handle(relation as i32, obj as *mut u32))
+            .ok_or_else(|| error("get_range", obj))?;
+        let mut val = vpi_user::t_vpi_value {
+            format: vpi_user::vpiIntVal as i32,
+            value: vpi_user::t_vpi_value__bindgen_ty_1 { integer: 0 },
+        };
+        vpi_user::vpi_get_value(expr, &mut val);
+        if val.format == vpi_user::vpiIntVal as i32 {
+            Ok(val.value.integer)
+        } else {
+            Err(error("get_range", obj))
+        }
+    }
+}
+
+fn iterate(kind: u32, handle: usize) -> Vec<usize> {
+    let mut list = Vec::new();
+    unsafe {
+        let iterator = vpi_user::vpi_iterate(kind as i32, handle as *mut u32);
+        // a null iterator means there are no such objects
+        if iterator.is_null() {
+            return list;
+        }
         loop {
             let obj = vpi_user::vpi_scan(iterator);
             match obj.is_null() {
@@ -430,14 +864,55 @@ pub fn discover_nets(handle: usize) -> Vec<usize> {
                 _ => list.push(obj as usize),
             };
         }
-        list
     }
+    list
 }
 
-fn check_null<T>(ptr: *mut T) -> SimpleResult<*mut T> {
+fn check_null<T>(ptr: *mut T) -> Option<*mut T> {
     if ptr.is_null() {
-        Err(())
+        None
     } else {
-        Ok(ptr)
+        Some(ptr)
+    }
+}
+
+fn full_name(obj: usize) -> Option<String> {
+    unsafe {
+        cstr_to_string(vpi_user::vpi_get_str(
+            vpi_user::vpiFullName as i32,
+            obj as *mut u32,
+        ))
+    }
+}
+
+// Message of the last VPI call, if it failed
+fn last_error() -> Option<String> {
+    let mut info = vpi_user::t_vpi_error_info::default();
+    unsafe {
+        match vpi_user::vpi_chk_error(&mut info) >= vpi_user::vpiError as i32 {
+            true => cstr_to_string(info.message),
+            false => None,
+        }
+    }
+}
+
+// Error of a failed `op` on `obj`, the message has to be read before any other VPI call
+fn error(op: &'static str, obj: usize) -> RstbError {
+    let message = last_error();
+    let object = match obj {
+        0 => String::new(),
+        _ => full_name(obj).unwrap_or_default(),
+    };
+    RstbError::sim(op, object, message)
+}
+
+fn check_error(op: &'static str, obj: usize) -> SimResult<()> {
+    match last_error() {
+        None => Ok(()),
+        Some(message) => Err(RstbError::sim(
+            op,
+            full_name(obj).unwrap_or_default(),
+            Some(message),
+        )),
     }
 }
@@ -1,38 +1,77 @@
 /* automatically generated by rust-bindgen 0.58.1 */
 
+pub const vpiConstant: u32 = 7;
 pub const vpiIntegerVar: u32 = 25;
+pub const vpiMemory: u32 = 29;
 pub const vpiMemoryWord: u32 = 30;
 pub const vpiModule: u32 = 32;
+pub const vpiNamedBegin: u32 = 33;
+pub const vpiNamedFork: u32 = 35;
 pub const vpiNet: u32 = 36;
 pub const vpiNetBit: u32 = 37;
+pub const vpiParameter: u32 = 41;
 pub const vpiPort: u32 = 44;
 pub const vpiPortBit: u32 = 45;
 pub const vpiRealVar: u32 = 47;
 pub const vpiReg: u32 = 48;
 pub const vpiRegBit: u32 = 49;
+pub const vpiSpecParam: u32 = 54;
+pub const vpiNetArray: u32 = 114;
+pub const vpiRegArray: u32 = 116;
+pub const vpiGenScope: u32 = 134;
+pub const vpiLeftRange: u32 = 79;
+pub const vpiRightRange: u32 = 83;
+pub const vpiSysTfCall: u32 = 85;
+pub const vpiArgument: u32 = 89;
+pub const vpiInternalScope: u32 = 92;
+pub const vpiVariables: u32 = 100;
 pub const vpiType: u32 = 1;
 pub const vpiName: u32 = 2;
 pub const vpiFullName: u32 = 3;
 pub const vpiSize: u32 = 4;
 pub const vpiDefName: u32 = 9;
 pub const vpiTimePrecision: u32 = 12;
+pub const vpiDirection: u32 = 20;
+pub const vpiInput: u32 = 1;
+pub const vpiOutput: u32 = 2;
+pub const vpiInout: u32 = 3;
 pub const vpiArray: u32 = 28;
+pub const vpiConstType: u32 = 40;
+pub const vpiRealConst: u32 = 2;
+pub const vpiStringConst: u32 = 6;
+pub const vpiIntFunc: u32 = 1;
+pub const vpiRealFunc: u32 = 2;
+pub const vpiSizedFunc: u32 = 4;
+pub const vpiSizedSignedFunc: u32 = 5;
 pub const vpiSigned: u32 = 65;
+pub const vpiStop: u32 = 66;
+pub const vpiFinish: u32 = 67;
+pub const vpiReset: u32 = 68;
 pub const vpiSimTime: u32 = 2;
 pub const vpiSuppressTime: u32 = 3;
 pub const vpiBinStrVal: u32 = 1;
 pub const vpiIntVal: u32 = 6;
+pub const vpiRealVal: u32 = 7;
 pub const vpiStringVal: u32 = 8;
+pub const vpiVectorVal: u32 = 9;
 pub const vpiSuppressVal: u32 = 13;
+pub const vpiNoDelay: u32 = 1;
 pub const vpiInertialDelay: u32 = 2;
+pub const vpiTransportDelay: u32 = 3;
 pub const vpiForceFlag: u32 = 5;
 pub const vpiReleaseFlag: u32 = 6;
+pub const vpiSysTask: u32 = 1;
+pub const vpiSysFunc: u32 = 2;
+pub const vpiError: u32 = 3;
 pub const cbValueChange: u32 = 1;
+pub const cbAtStartOfSimTime: u32 = 5;
 pub const cbReadWriteSynch: u32 = 6;
 pub const cbReadOnlySynch: u32 = 7;
+pub const cbNextSimTime: u32 = 8;
 pub const cbAfterDelay: u32 = 9;
 pub const cbStartOfSimulation: u32 = 11;
 pub const cbEndOfSimulation: u32 = 12;
+pub const cbNBASynch: u32 = 30;
 pub type PLI_INT32 = ::std::os::raw::c_int;
 pub type PLI_UINT32 = ::std::os::raw::c_uint;
 pub type PLI_BYTE8 = ::std::os::raw::c_char;
@@ -90,6 +129,56 @@ impl Default for t_vpi_value {
     }
 }
 pub type p_vpi_value = *mut t_vpi_value;
+#[doc = " system task/function structure"]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct t_vpi_systf_data {
+    pub type_: PLI_INT32,
+    pub sysfunctype: PLI_INT32,
+    pub tfname: *const PLI_BYTE8,
+    pub calltf: ::std::option::Option<unsafe extern "C" fn(arg1: *mut PLI_BYTE8) -> PLI_INT32>,
+    pub compiletf:
+        ::std::option::Option<unsafe extern "C" fn(arg1: *mut PLI_BYTE8) -> PLI_INT32>,
+    pub sizetf: ::std::option::Option<unsafe extern "C" fn(arg1: *mut PLI_BYTE8) -> PLI_INT32>,
+    pub user_data: *mut PLI_BYTE8,
+}
+impl Default for t_vpi_systf_data {
+    fn default() -> Self {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type p_vpi_systf_data = *mut t_vpi_systf_data;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct t_vpi_vlog_info {
+    pub argc: PLI_INT32,
+    pub argv: *mut *mut PLI_BYTE8,
+    pub product: *mut PLI_BYTE8,
+    pub version: *mut PLI_BYTE8,
+}
+impl Default for t_vpi_vlog_info {
+    fn default() -> Self {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type p_vpi_vlog_info = *mut t_vpi_vlog_info;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct t_vpi_error_info {
+    pub state: PLI_INT32,
+    pub level: PLI_INT32,
+    pub message: *mut PLI_BYTE8,
+    pub product: *mut PLI_BYTE8,
+    pub code: *mut PLI_BYTE8,
+    pub file: *mut PLI_BYTE8,
+    pub line: PLI_INT32,
+}
+impl Default for t_vpi_error_info {
+    fn default() -> Self {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type p_vpi_error_info = *mut t_vpi_error_info;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct t_cb_data {
@@ -114,9 +203,18 @@ extern "C" {
 extern "C" {
     pub fn vpi_remove_cb(cb_obj: vpiHandle) -> PLI_INT32;
 }
+extern "C" {
+    pub fn vpi_register_systf(systf_data_p: p_vpi_systf_data) -> vpiHandle;
+}
 extern "C" {
     pub fn vpi_handle_by_name(name: *mut PLI_BYTE8, scope: vpiHandle) -> vpiHandle;
 }
+extern "C" {
+    pub fn vpi_handle_by_index(object: vpiHandle, indx: PLI_INT32) -> vpiHandle;
+}
+extern "C" {
+    pub fn vpi_handle(type_: PLI_INT32, refHandle: vpiHandle) -> vpiHandle;
+}
 extern "C" {
     pub fn vpi_iterate(type_: PLI_INT32, refHandle: vpiHandle) -> vpiHandle;
 }
@@ -146,6 +244,15 @@ extern "C" {
 extern "C" {
     pub fn vpi_printf(format: *const PLI_BYTE8, ...) -> PLI_INT32;
 }
+extern "C" {
+    pub fn vpi_chk_error(error_info_p: p_vpi_error_info) -> PLI_INT32;
+}
 extern "C" {
     pub fn vpi_free_object(object: vpiHandle) -> PLI_INT32;
 }
+extern "C" {
+    pub fn vpi_get_vlog_info(vlog_info_p: p_vpi_vlog_info) -> PLI_INT32;
+}
+extern "C" {
+    pub fn vpi_control(operation: PLI_INT32, ...) -> PLI_INT32;
+}